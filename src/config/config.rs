@@ -0,0 +1,174 @@
+use std::{env, fs, path::Path, process, time::Duration};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::storage::StorageKind,
+    utils::error::{Error, Result},
+};
+
+const DEFAULT_CONFIG_PATH: &str = "config.json";
+
+lazy_static! {
+    /// Loaded from the file in the `GROWRUST_CONFIG` environment variable, or `config.json`.
+    pub static ref CONFIG: ServerConfig = {
+        let path = env::var("GROWRUST_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+
+        match ServerConfig::load_from_file(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Error: Could not load config '{}': {}", path, e);
+                process::exit(1);
+            }
+        }
+    };
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub port: u16,
+    pub max_peers: usize,
+
+    pub min_packet_size: usize,
+    pub max_packet_size: usize,
+
+    pub items_path: String,
+    pub drops_path: String, // optional per item drop tables
+
+    pub storage: StorageKind,
+    pub worlds_path: String,  // file storage only
+    pub players_path: String, // file storage only
+    pub bans_path: String,    // file storage only
+    pub sqlite_path: String,  // sqlite storage only
+    pub backup_count: usize,  // file storage only
+
+    pub world_width: u32,
+    pub world_height: u32,
+
+    pub max_items_count: u16, // for items that don't have their own maximum in items.dat
+    pub autosave_interval_secs: u64,
+
+    pub cdn_host: String,
+    pub cdn_path: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 10000,
+            max_peers: 1024,
+
+            min_packet_size: 4,
+            max_packet_size: 1024,
+
+            items_path: "data/items.dat".to_string(),
+            drops_path: "data/drops.json".to_string(),
+
+            storage: StorageKind::File,
+            worlds_path: "data/worlds".to_string(),
+            players_path: "data/players".to_string(),
+            bans_path: "data/bans.json".to_string(),
+            sqlite_path: "data/growrust.db".to_string(),
+            backup_count: 3,
+
+            world_width: 100,
+            world_height: 60,
+
+            max_items_count: 200,
+            autosave_interval_secs: 5 * 60,
+
+            cdn_host: "ubistatic-a.akamaihd.net".to_string(),
+            cdn_path: "0098/67197/cache/".to_string(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Writes the default config to `path` first if it doesn't exist yet.
+    pub fn load_from_file(path: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            let config = Self::default();
+            fs::write(path, serde_json::to_string_pretty(&config)?)?;
+
+            println!("Created default config in '{}'.", path);
+            return Ok(config);
+        }
+
+        let content = fs::read_to_string(path)?;
+        let config = serde_json::from_str::<Self>(&content)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let err = |message: &str| Err(Error::ConfigError(message.to_string()));
+
+        if self.port == 0 {
+            return err("port cannot be 0");
+        }
+
+        // enet can't address more than 0xFFF peers
+        if self.max_peers == 0 || self.max_peers > 0xFFF {
+            return err("max_peers must be between 1 and 4095");
+        }
+
+        if self.min_packet_size >= self.max_packet_size {
+            return err("min_packet_size must be smaller than max_packet_size");
+        }
+
+        if self.items_path.is_empty() {
+            return err("items_path cannot be empty");
+        }
+
+        match self.storage {
+            StorageKind::File
+                if self.worlds_path.is_empty()
+                    || self.players_path.is_empty()
+                    || self.bans_path.is_empty() =>
+            {
+                return err(
+                    "worlds_path, players_path and bans_path cannot be empty with file storage",
+                );
+            }
+            StorageKind::Sqlite if self.sqlite_path.is_empty() => {
+                return err("sqlite_path cannot be empty with sqlite storage");
+            }
+            _ => {}
+        }
+
+        if self.world_width < 10 || self.world_height < 20 {
+            return err("worlds must be at least 10 tiles wide and 20 tiles high");
+        }
+
+        // lock parents are stored as u16 tile indexes
+        match self.world_width.checked_mul(self.world_height) {
+            Some(tiles) if tiles <= u16::MAX as u32 => {}
+            _ => return err("world_width * world_height cannot be bigger than 65535"),
+        }
+
+        // the client only has a byte for the count of a stack
+        if self.max_items_count == 0 || self.max_items_count > u8::MAX as u16 {
+            return err("max_items_count must be between 1 and 255");
+        }
+
+        if self.autosave_interval_secs == 0 {
+            return err("autosave_interval_secs cannot be 0");
+        }
+
+        if self.cdn_host.is_empty() || self.cdn_path.is_empty() {
+            return err("cdn_host and cdn_path cannot be empty");
+        }
+
+        Ok(())
+    }
+
+    pub fn autosave_interval(&self) -> Duration {
+        Duration::from_secs(self.autosave_interval_secs)
+    }
+
+    // make sure it gets initialized at startup!
+    pub fn touch(&self) {}
+}
@@ -0,0 +1,105 @@
+use std::{
+    io::{self, BufRead},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::{
+    database::playerdb,
+    player::{player::Player, role::Role, session_manager::SessionManager},
+    utils::{
+        error::{Error, Result},
+        variant_function::VariantFunction::*,
+    },
+    world::world_manager::WorldManager,
+};
+
+/// Lines typed into the server console. Stdin is read on its own thread so the main loop never blocks on it.
+pub struct Console {
+    receiver: Receiver<String>,
+}
+
+impl Console {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                match line {
+                    Ok(line) => {
+                        if sender.send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Self { receiver }
+    }
+
+    pub fn poll(&self) -> Option<String> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+pub fn handle(line: &str, sessions: &mut SessionManager, world_manager: &mut WorldManager) {
+    let args: Vec<&str> = line.split_whitespace().collect();
+
+    match args.as_slice() {
+        [] => {}
+
+        ["help"] => {
+            println!("grant <name> <role> - gives a player a role");
+            println!("revoke <name> - makes a player a normal player again");
+            println!("roles: {}", Role::ALL.map(|role| role.name()).join(", "));
+        }
+
+        ["grant", name, role] => match Role::from_name(role) {
+            Some(role) => set_role(sessions, world_manager, name, role),
+            None => println!("There is no role called {}, type help to see them.", role),
+        },
+
+        ["revoke", name] => set_role(sessions, world_manager, name, Role::Player),
+
+        _ => println!("Unknown command, type help to see all commands."),
+    }
+}
+
+fn set_role(
+    sessions: &mut SessionManager,
+    world_manager: &mut WorldManager,
+    name: &str,
+    role: Role,
+) {
+    let result = match sessions.get_by_name(name, None) {
+        Some(player) => set_online_role(player, world_manager, role),
+        None => playerdb::set_offline_role(name, role),
+    };
+
+    match result {
+        Ok(_) => println!("{} is now a {}.", name, role.name()),
+        Err(Error::NotFound) => println!("There is no player named {}.", name),
+        Err(e) => println!("Failed to set the role of {}! Error: {:?}", name, e),
+    }
+}
+
+fn set_online_role(
+    player: &mut Player,
+    world_manager: &mut WorldManager,
+    role: Role,
+) -> Result<()> {
+    player.role = role;
+    player.dirty = true;
+    player.send_log(&format!("`oYou are now a `w{}``.``", role.name()));
+
+    // so everyone in the world sees the new prefix right away
+    if let Ok(world) = player.get_world(world_manager) {
+        let mut world = world.borrow_mut();
+        world.push_varfn_v(OnNameChanged(&player.display()), player.net_id, -1);
+        world.send_all();
+    }
+
+    playerdb::save_player(player)
+}
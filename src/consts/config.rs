@@ -1,2 +0,0 @@
-pub const MINIMUM_PACKET_SIZE: usize = 4;
-pub const MAXIMUM_PACKET_SIZE: usize = 1024;
@@ -0,0 +1 @@
+pub const PUBLIC: u8 = 1 << 0; // anyone can build, only the owner can break the lock
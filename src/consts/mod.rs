@@ -1,15 +1,14 @@
-pub mod config;
 pub mod item_clothing;
 pub mod item_flags;
 pub mod item_material;
 pub mod item_type;
 pub mod item_visual;
 pub mod items;
+pub mod lock_flags;
 pub mod message_type;
 pub mod packet;
 pub mod packet_flags;
 pub mod packet_type;
+pub mod permissions;
 pub mod tile_flags;
 pub mod tileextra_type;
-
-pub const MAXIMUM_ITEMS_COUNT: u8 = 200;
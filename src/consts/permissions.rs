@@ -0,0 +1,9 @@
+// what a player is allowed to do, granted by their `Role` and checked by `Player::has_permission`
+pub const NONE: u32 = 0;
+pub const GIVE_ITEMS: u32 = 1 << 0;
+pub const BYPASS_LOCKS: u32 = 1 << 1; // build and break in every world
+pub const KICK: u32 = 1 << 2;
+pub const MUTE: u32 = 1 << 3;
+pub const BAN: u32 = 1 << 4;
+
+pub const ALL: u32 = u32::MAX;
@@ -1 +1,9 @@
 pub const DOOR: u8 = 0x1;
+pub const SIGN: u8 = 0x2;
+pub const LOCK: u8 = 0x3;
+pub const SEED: u8 = 0x4;
+pub const MAILBOX: u8 = 0x6;
+pub const BULLETIN: u8 = 0x7;
+pub const DISPLAY_BLOCK: u8 = 0x17;
+pub const VENDING_MACHINE: u8 = 0x18;
+pub const STORAGE: u8 = 0x1A;
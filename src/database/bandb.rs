@@ -0,0 +1,245 @@
+use std::sync::{Mutex, MutexGuard};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    player::player::Player,
+    utils::{self, error::Result},
+};
+
+use super::storage::storage;
+
+// what most devices send instead of their real mac address, banning it would ban everyone
+const PLACEHOLDER_MAC: &str = "02:00:00:00:00:00";
+
+lazy_static! {
+    pub static ref BAN_LIST: Mutex<Vec<Punishment>> = Mutex::new(match storage().load_bans() {
+        Ok(bans) => bans,
+        Err(e) => {
+            println!("Failed to load the ban list! Error: {:?}", e);
+            Vec::new()
+        }
+    });
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PunishmentKind {
+    Ban,
+    Mute,
+}
+
+/// A ban or mute of an account, bans also cover the `mac` and `rid` the player was online with.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Punishment {
+    pub kind: PunishmentKind,
+    pub name: String,
+    #[serde(default)]
+    pub mac: String,
+    #[serde(default)]
+    pub rid: String,
+    pub reason: String,
+    pub by: String,
+    pub until: Option<u64>, // unix time, forever if none
+}
+
+impl Punishment {
+    pub fn new(
+        kind: PunishmentKind,
+        target: &str,
+        by: &str,
+        reason: &str,
+        secs: Option<u64>,
+    ) -> Self {
+        Self {
+            kind,
+            name: target.to_string(),
+            mac: String::new(),
+            rid: String::new(),
+            reason: reason.to_string(),
+            by: by.to_string(),
+            until: secs.map(|secs| utils::unix_time() + secs),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.until
+            .map_or(false, |until| until <= utils::unix_time())
+    }
+
+    /// "forever" for permanent ones.
+    pub fn remaining(&self) -> String {
+        match self.until {
+            Some(until) => utils::format_duration(until.saturating_sub(utils::unix_time())),
+            None => "forever".to_string(),
+        }
+    }
+
+    fn applies_to(&self, player: &Player) -> bool {
+        let matches = |banned: &str, value: &str| !banned.is_empty() && banned == value;
+
+        (!player.name.is_empty() && self.name.eq_ignore_ascii_case(&player.name))
+            || (matches(&self.mac, &player.mac_address) && self.mac != PLACEHOLDER_MAC)
+            || matches(&self.rid, &player.rid)
+    }
+}
+
+fn ban_list() -> MutexGuard<'static, Vec<Punishment>> {
+    BAN_LIST.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// expired punishments are only dropped when the list gets saved anyway
+fn save(bans: &mut Vec<Punishment>) -> Result<()> {
+    prune(bans);
+    storage().save_bans(bans)
+}
+
+fn prune(bans: &mut Vec<Punishment>) {
+    bans.retain(|x| !x.is_expired());
+}
+
+fn find_in<'a>(
+    bans: &'a [Punishment],
+    kind: PunishmentKind,
+    player: &Player,
+) -> Option<&'a Punishment> {
+    bans.iter()
+        .find(|x| x.kind == kind && !x.is_expired() && x.applies_to(player))
+}
+
+pub fn find(kind: PunishmentKind, player: &Player) -> Option<Punishment> {
+    find_in(&ban_list(), kind, player).cloned()
+}
+
+/// Replaces whatever punishment of the same kind the account already had.
+pub fn add(punishment: Punishment) -> Result<()> {
+    let mut bans = ban_list();
+    bans.retain(|x| x.kind != punishment.kind || !x.name.eq_ignore_ascii_case(&punishment.name));
+    bans.push(punishment);
+    save(&mut bans)
+}
+
+/// Whether the account had one to remove.
+pub fn remove(kind: PunishmentKind, name: &str) -> Result<bool> {
+    let mut bans = ban_list();
+    let count = bans.len();
+    bans.retain(|x| x.kind != kind || !x.name.eq_ignore_ascii_case(name) || x.is_expired());
+
+    if bans.len() == count {
+        return Ok(false);
+    }
+
+    save(&mut bans)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expires_only_when_timed() {
+        let forever = Punishment::new(PunishmentKind::Mute, "kevz", "admin", "spam", None);
+        assert!(!forever.is_expired());
+        assert_eq!(forever.remaining(), "forever");
+
+        let timed = Punishment::new(PunishmentKind::Mute, "kevz", "admin", "spam", Some(60));
+        assert!(!timed.is_expired());
+
+        let over = Punishment {
+            until: Some(utils::unix_time() - 1),
+            ..timed
+        };
+        assert!(over.is_expired());
+    }
+
+    #[test]
+    fn applies_by_name_mac_or_rid() {
+        let ban = Punishment {
+            mac: "aa:bb:cc:dd:ee:ff".to_string(),
+            rid: "RID1".to_string(),
+            ..Punishment::new(PunishmentKind::Ban, "Kevz", "admin", "test", None)
+        };
+
+        let by_name = Player {
+            name: "kevz".to_string(),
+            ..Default::default()
+        };
+        let by_mac = Player {
+            name: "alt".to_string(),
+            mac_address: "aa:bb:cc:dd:ee:ff".to_string(),
+            ..Default::default()
+        };
+        let by_rid = Player {
+            name: "alt".to_string(),
+            rid: "RID1".to_string(),
+            ..Default::default()
+        };
+        let other = Player {
+            name: "alt".to_string(),
+            mac_address: "11:22:33:44:55:66".to_string(),
+            rid: "RID2".to_string(),
+            ..Default::default()
+        };
+
+        assert!(ban.applies_to(&by_name));
+        assert!(ban.applies_to(&by_mac));
+        assert!(ban.applies_to(&by_rid));
+        assert!(!ban.applies_to(&other));
+    }
+
+    #[test]
+    fn ignores_empty_values_and_the_placeholder_mac() {
+        let ban = Punishment {
+            mac: PLACEHOLDER_MAC.to_string(),
+            ..Punishment::new(PunishmentKind::Ban, "kevz", "admin", "test", None)
+        };
+
+        let placeholder = Player {
+            name: "alt".to_string(),
+            mac_address: PLACEHOLDER_MAC.to_string(),
+            ..Default::default()
+        };
+
+        assert!(!ban.applies_to(&placeholder));
+        assert!(!ban.applies_to(&Player::default()));
+    }
+
+    #[test]
+    fn find_skips_expired_and_other_kinds() {
+        let ban = Punishment::new(PunishmentKind::Ban, "kevz", "admin", "test", None);
+        let mut bans = vec![
+            Punishment {
+                until: Some(utils::unix_time() - 1),
+                ..ban.clone()
+            },
+            Punishment::new(PunishmentKind::Mute, "kevz", "admin", "spam", None),
+        ];
+
+        let kevz = Player {
+            name: "kevz".to_string(),
+            ..Default::default()
+        };
+        assert!(find_in(&bans, PunishmentKind::Ban, &kevz).is_none());
+        assert!(find_in(&bans, PunishmentKind::Mute, &kevz).is_some());
+
+        bans.push(ban);
+        assert!(find_in(&bans, PunishmentKind::Ban, &kevz).is_some());
+    }
+
+    #[test]
+    fn prunes_expired() {
+        let mut bans = vec![
+            Punishment::new(PunishmentKind::Ban, "old", "admin", "test", Some(60)),
+            Punishment::new(PunishmentKind::Ban, "kevz", "admin", "test", None),
+            Punishment::new(PunishmentKind::Ban, "timed", "admin", "test", Some(60)),
+        ];
+        bans[0].until = Some(utils::unix_time() - 1);
+
+        prune(&mut bans);
+
+        let names: Vec<_> = bans.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, ["kevz", "timed"]);
+    }
+}
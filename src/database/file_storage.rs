@@ -0,0 +1,308 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::utils::{
+    error::{Error, Result},
+    file,
+};
+
+use super::{
+    bandb::Punishment,
+    playerdb::{self, PlayerData},
+    storage::Storage,
+};
+
+/// One json file per player, one bin file per world, text files with the next user id and the name of every user id,
+/// and a json file with the ban list. Every file is written atomically, and the last `backups` versions of each player
+/// and world are kept next to it in case the current one can't be read anymore.
+pub struct FileStorage {
+    players_path: String,
+    worlds_path: String,
+    bans_path: String,
+    backups: usize,
+    names: RefCell<HashMap<i32, String>>, // user id -> name, mirrors names.txt
+}
+
+impl FileStorage {
+    pub fn new(
+        players_path: &str,
+        worlds_path: &str,
+        bans_path: &str,
+        backups: usize,
+    ) -> Result<Self> {
+        fs::create_dir_all(players_path)?;
+        fs::create_dir_all(worlds_path)?;
+        if let Some(parent) = Path::new(bans_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let storage = Self {
+            players_path: players_path.to_string(),
+            worlds_path: worlds_path.to_string(),
+            bans_path: bans_path.to_string(),
+            backups,
+            names: RefCell::new(HashMap::new()),
+        };
+        storage.move_legacy_bans()?;
+        storage.load_names()?;
+
+        Ok(storage)
+    }
+
+    fn names_path(&self) -> PathBuf {
+        Path::new(&self.players_path).join("names.txt")
+    }
+
+    // one "<user id> <name>" per line, built from the player files the first time
+    fn load_names(&self) -> Result<()> {
+        let content = match fs::read_to_string(self.names_path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return self.rebuild_names(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut names = self.names.borrow_mut();
+        for line in content.lines() {
+            let (user_id, name) = line.split_once(' ').ok_or(Error::CorruptedData)?;
+            names.insert(user_id.parse()?, name.to_string());
+        }
+
+        Ok(())
+    }
+
+    fn rebuild_names(&self) -> Result<()> {
+        for name in self.list_players()? {
+            // one broken file shouldn't hide everyone else
+            if let Ok(data) = self.load_player(&name) {
+                self.names.borrow_mut().insert(data.user_id, data.name);
+            }
+        }
+
+        self.save_names()
+    }
+
+    fn save_names(&self) -> Result<()> {
+        let names = self.names.borrow();
+        let mut user_ids: Vec<_> = names.keys().collect();
+        user_ids.sort();
+
+        let mut content = String::new();
+        for user_id in user_ids {
+            content += &format!("{} {}\n", user_id, names[user_id]);
+        }
+
+        file::write_atomic(&self.names_path(), content.as_bytes(), 0)?;
+        Ok(())
+    }
+
+    // the ban list used to be kept between the players, where it looked like a player called "bans"
+    fn move_legacy_bans(&self) -> Result<()> {
+        let legacy = Path::new(&self.players_path).join("bans.json");
+        if Path::new(&self.bans_path).exists() || !legacy.exists() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&legacy)?;
+        if serde_json::from_str::<Vec<Punishment>>(&content).is_ok() {
+            fs::rename(&legacy, &self.bans_path)?;
+            println!("Moved the ban list to '{}'.", self.bans_path);
+        }
+
+        Ok(())
+    }
+
+    fn player_path(&self, name: &str) -> PathBuf {
+        Path::new(&self.players_path).join(format!("{}.json", name))
+    }
+
+    fn world_path(&self, name: &str) -> PathBuf {
+        Path::new(&self.worlds_path).join(format!("{}.bin", name))
+    }
+
+    fn read_player(path: &Path) -> Result<PlayerData> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str::<PlayerData>(&content)?)
+    }
+}
+
+impl Storage for FileStorage {
+    fn player_exists(&self, name: &str) -> Result<bool> {
+        Ok(self.player_path(name).exists())
+    }
+
+    fn create_player(&self, data: &PlayerData) -> Result<i32> {
+        if self.player_exists(&data.name)? {
+            return Err(Error::NameAlreadyExists);
+        }
+
+        let userid_path = Path::new(&self.players_path).join("userid.txt");
+        let user_id = match fs::read_to_string(&userid_path) {
+            Ok(content) => content.trim().parse::<i32>()?,
+            Err(_) => 1,
+        };
+
+        file::write_atomic(&userid_path, (user_id + 1).to_string().as_bytes(), 0)?;
+
+        let data = PlayerData {
+            user_id,
+            ..data.clone()
+        };
+        self.save_player(&data)?;
+
+        self.names.borrow_mut().insert(user_id, data.name);
+        self.save_names()?;
+
+        Ok(user_id)
+    }
+
+    fn load_player(&self, name: &str) -> Result<PlayerData> {
+        let path = self.player_path(name);
+        let err = match Self::read_player(&path) {
+            Ok(data) => return Ok(data),
+            Err(e) => e,
+        };
+
+        for backup in file::backups(&path, self.backups) {
+            if let Ok(data) = Self::read_player(&backup) {
+                println!(
+                    "Player {} could not be loaded ({:?}), using backup {:?}.",
+                    name, err, backup
+                );
+                return Ok(data);
+            }
+        }
+
+        Err(err)
+    }
+
+    fn save_player(&self, data: &PlayerData) -> Result<()> {
+        file::write_atomic(
+            &self.player_path(&data.name),
+            serde_json::to_string_pretty(data)?.as_bytes(),
+            self.backups,
+        )?;
+
+        Ok(())
+    }
+
+    fn list_players(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+
+        for entry in fs::read_dir(&self.players_path)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |x| x == "json") {
+                if let Some(name) = path.file_stem().and_then(|x| x.to_str()) {
+                    if !playerdb::is_reserved_name(name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+
+        names.sort();
+        Ok(names)
+    }
+
+    fn find_player_name(&self, user_id: i32) -> Result<String> {
+        self.names
+            .borrow()
+            .get(&user_id)
+            .cloned()
+            .ok_or(Error::NotFound)
+    }
+
+    fn load_bans(&self) -> Result<Vec<Punishment>> {
+        match fs::read_to_string(&self.bans_path) {
+            Ok(content) => Ok(serde_json::from_str::<Vec<Punishment>>(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_bans(&self, bans: &[Punishment]) -> Result<()> {
+        file::write_atomic(
+            Path::new(&self.bans_path),
+            serde_json::to_string_pretty(bans)?.as_bytes(),
+            self.backups,
+        )?;
+
+        Ok(())
+    }
+
+    fn world_exists(&self, name: &str) -> Result<bool> {
+        let path = self.world_path(name);
+        Ok(path.exists() || !file::backups(&path, self.backups).is_empty())
+    }
+
+    fn load_world(&self, name: &str) -> Result<Vec<u8>> {
+        Ok(fs::read(self.world_path(name))?)
+    }
+
+    fn load_world_backups(&self, name: &str) -> Result<Vec<Vec<u8>>> {
+        let mut backups = Vec::new();
+        for path in file::backups(&self.world_path(name), self.backups) {
+            backups.push(fs::read(path)?);
+        }
+
+        Ok(backups)
+    }
+
+    fn save_world(&self, name: &str, data: &[u8]) -> Result<()> {
+        file::write_atomic(&self.world_path(name), data, self.backups)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use crate::player::player::Player;
+
+    use super::*;
+
+    #[test]
+    fn finds_names_through_the_index() {
+        let dir = env::temp_dir().join(format!("file_storage_{}", process::id()));
+        let open = || {
+            FileStorage::new(
+                dir.join("players").to_str().unwrap(),
+                dir.join("worlds").to_str().unwrap(),
+                dir.join("bans.json").to_str().unwrap(),
+                0,
+            )
+            .unwrap()
+        };
+
+        let storage = open();
+        let create = |name: &str| {
+            storage
+                .create_player(&PlayerData {
+                    name: name.to_string(),
+                    ..PlayerData::from_player(&Player::default())
+                })
+                .unwrap()
+        };
+        let kevz = create("kevz");
+        let alt = create("alt");
+
+        assert_eq!(storage.find_player_name(kevz).unwrap(), "kevz");
+        assert_eq!(storage.find_player_name(alt).unwrap(), "alt");
+        assert!(matches!(
+            storage.find_player_name(alt + 1),
+            Err(Error::NotFound)
+        ));
+
+        // kept across restarts, and rebuilt from the player files if it's gone
+        assert_eq!(open().find_player_name(kevz).unwrap(), "kevz");
+        fs::remove_file(storage.names_path()).unwrap();
+        assert_eq!(open().find_player_name(alt).unwrap(), "alt");
+        assert!(storage.names_path().exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,2 +1,6 @@
+pub mod bandb;
+pub mod file_storage;
 pub mod playerdb;
+pub mod sqlite_storage;
+pub mod storage;
 pub mod worlddb;
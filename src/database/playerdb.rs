@@ -1,41 +1,112 @@
-use std::{collections::HashMap, fs, path::Path};
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    player::{clothing::Clothing, inventoryitem::InventoryItem, player::Player},
-    utils::error::{Error, Result},
+    player::{
+        clothing::Clothing,
+        friend::Friend,
+        inventoryitem::InventoryItem,
+        mail::{Mail, MAX_MAIL},
+        player::{Player, DEFAULT_ITEMS_SLOTS},
+        role::Role,
+    },
+    utils::{
+        self,
+        error::{Error, Result},
+        password,
+    },
 };
 
-#[derive(Serialize, Deserialize)]
+use super::storage::storage;
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct PlayerData {
     pub name: String,
-    pub pass: String,
+    #[serde(alias = "pass")]
+    pub pass_hash: String,
     pub net_id: i32,
     pub user_id: i32,
     pub cloth: Clothing,
     pub items: HashMap<u16, InventoryItem>,
+    #[serde(default = "default_items_slots")]
+    pub items_slots: u32,
+    #[serde(default)]
+    pub gems: i32,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub blocked: Vec<i32>,
+    #[serde(default)]
+    pub pm_muted: bool,
+    #[serde(default)]
+    pub mail: Vec<Mail>,
+    #[serde(default)]
+    pub friends: Vec<Friend>,
 }
 
-pub fn create_player_database(player: &mut Player) -> Result<()> {
-    if !Path::new("data/players").exists() {
-        fs::create_dir("data/players")?;
+impl PlayerData {
+    pub fn from_player(player: &Player) -> Self {
+        let mut data = PlayerData {
+            name: player.name.to_owned(),
+            pass_hash: player.pass_hash.to_owned(),
+            cloth: player.cloth,
+            items: HashMap::new(),
+            items_slots: player.items_slots,
+            net_id: player.net_id,
+            user_id: player.user_id,
+            gems: player.gems,
+            role: player.role,
+            blocked: player.blocked.to_owned(),
+            pm_muted: player.pm_muted,
+            mail: player.mail.to_owned(),
+            friends: player.friends.to_owned(),
+        };
+
+        for (key, pair) in player.items.iter() {
+            data.items.insert(*key, *pair);
+        }
+
+        data
     }
 
-    if !Path::new("data/players/userid.txt").exists() {
-        fs::write("data/players/userid.txt", "1")?;
+    pub fn apply_to(self, player: &mut Player) {
+        player.name = self.name;
+        player.pass_hash = self.pass_hash;
+        player.cloth = self.cloth;
+        player.items = self.items;
+        player.items_slots = self.items_slots;
+        player.net_id = self.net_id;
+        player.user_id = self.user_id;
+        player.gems = self.gems;
+        player.role = self.role;
+        player.blocked = self.blocked;
+        player.pm_muted = self.pm_muted;
+        player.mail = self.mail;
+        player.friends = self.friends;
     }
+}
+
+// names of files that aren't players, nobody can register them
+const RESERVED_NAMES: &[&str] = &["bans", "userid"];
 
-    if Path::new(&format!("data/players/{}.json", player.name)).exists() {
+pub fn is_reserved_name(name: &str) -> bool {
+    RESERVED_NAMES.contains(&name.to_lowercase().as_str())
+}
+
+// saves from before backpack upgrades
+fn default_items_slots() -> u32 {
+    DEFAULT_ITEMS_SLOTS
+}
+
+pub fn create_player_database(player: &mut Player) -> Result<()> {
+    if is_reserved_name(&player.name) || storage().player_exists(&player.name)? {
         return Err(Error::NameAlreadyExists);
     }
 
-    let content = fs::read_to_string("data/players/userid.txt")?;
-    let user_id = content.parse::<i32>()?;
-    player.user_id = user_id;
-
-    fs::write("data/players/userid.txt", (player.user_id + 1).to_string())?;
-    save_player(player)?;
+    player.pass_hash = password::hash(&player.pass)?;
+    player.user_id = storage().create_player(&PlayerData::from_player(player))?;
+    player.dirty = false;
 
     Ok(())
 }
@@ -44,53 +115,151 @@ pub fn authenticate_player(player: &mut Player) -> Result<()> {
     let pass = player.pass.to_owned(); // cache it first.
     load_player(player)?;
 
-    if player.pass != pass {
-        return Err(Error::WrongPassword);
+    if password::is_hashed(&player.pass_hash) {
+        if !password::verify(&pass, &player.pass_hash)? {
+            return Err(Error::WrongPassword);
+        }
+    } else {
+        if player.pass_hash != pass {
+            return Err(Error::WrongPassword);
+        }
+
+        // legacy plaintext account, now that we know the password is right we can hash it
+        player.pass_hash = password::hash(&pass)?;
+        save_player(player)?;
     }
 
     Ok(())
 }
 
-pub fn load_player(player: &mut Player) -> Result<()> {
-    if !Path::new("data/players").exists() {
-        fs::create_dir("data/players")?;
-    }
+pub fn find_user_id(name: &str) -> Result<i32> {
+    Ok(storage().load_player(name)?.user_id)
+}
+
+/// The user id and the name as it was registered, which can differ from `name` in case.
+pub fn find_account(name: &str) -> Result<(i32, String)> {
+    let data = storage().load_player(name)?;
+    Ok((data.user_id, data.name))
+}
 
-    let content = fs::read_to_string(format!("data/players/{}.json", player.name))?;
-    let data = serde_json::from_str::<PlayerData>(&content)?;
+pub fn find_role(name: &str) -> Result<Role> {
+    Ok(storage().load_player(name)?.role)
+}
+
+pub fn find_name(user_id: i32) -> Result<String> {
+    storage().find_player_name(user_id)
+}
 
-    player.name = data.name;
-    player.pass = data.pass;
-    player.cloth = data.cloth;
-    player.items = data.items;
-    player.net_id = data.net_id;
-    player.user_id = data.user_id;
+pub fn load_player(player: &mut Player) -> Result<()> {
+    let data = storage().load_player(&player.name)?;
+    data.apply_to(player);
 
     Ok(())
 }
 
 pub fn save_player(player: &mut Player) -> Result<()> {
-    if !Path::new("data/players").exists() {
-        fs::create_dir("data/players")?;
+    storage().save_player(&PlayerData::from_player(player))?;
+    player.dirty = false;
+
+    Ok(())
+}
+
+/// Leaves a private message for a player that isn't online. False if they don't accept messages
+/// from `from` or their mailbox is full.
+pub fn send_mail(name: &str, from: &Player, text: &str) -> Result<bool> {
+    let mut data = storage().load_player(name)?;
+    if !deliver_mail(&mut data, from, text) {
+        return Ok(false);
     }
 
-    let mut data = PlayerData {
-        name: player.name.to_owned(),
-        pass: player.pass.to_owned(),
-        cloth: player.cloth,
-        items: HashMap::new(),
-        net_id: player.net_id,
-        user_id: player.user_id,
-    };
-
-    for (key, pair) in player.items.iter() {
-        data.items.insert(*key, *pair);
+    storage().save_player(&data).map(|_| true)
+}
+
+fn deliver_mail(data: &mut PlayerData, from: &Player, text: &str) -> bool {
+    if data.pm_muted || data.blocked.contains(&from.user_id) || data.mail.len() >= MAX_MAIL {
+        return false;
     }
 
-    fs::write(
-        format!("data/players/{}.json", data.name),
-        serde_json::to_string_pretty(&data)?,
-    )?;
+    data.mail.push(Mail {
+        from: from.name.to_owned(),
+        text: text.to_string(),
+        sent_at: utils::unix_time(),
+    });
+    true
+}
 
-    Ok(())
+/// Changes a player that isn't online, online ones have to be changed directly since they'd overwrite it on their next save.
+pub fn update_offline_player<F: FnOnce(&mut PlayerData)>(name: &str, update: F) -> Result<()> {
+    let mut data = storage().load_player(name)?;
+    update(&mut data);
+    storage().save_player(&data)
+}
+
+pub fn set_offline_role(name: &str, role: Role) -> Result<()> {
+    update_offline_player(name, |data| data.role = role)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivers_mail() {
+        let mut data = PlayerData::from_player(&Player::default());
+        let from = Player {
+            name: "kevz".to_string(),
+            ..Default::default()
+        };
+
+        assert!(deliver_mail(&mut data, &from, "hi"));
+        assert_eq!(data.mail.len(), 1);
+        assert_eq!(data.mail[0].from, "kevz");
+        assert_eq!(data.mail[0].text, "hi");
+        assert!(data.mail[0].sent_at <= utils::unix_time());
+    }
+
+    #[test]
+    fn refuses_muted_and_blocked() {
+        let from = Player {
+            user_id: 7,
+            ..Default::default()
+        };
+
+        let mut muted = PlayerData {
+            pm_muted: true,
+            ..PlayerData::from_player(&Player::default())
+        };
+        assert!(!deliver_mail(&mut muted, &from, "hi"));
+        assert!(muted.mail.is_empty());
+
+        let mut blocked = PlayerData {
+            blocked: vec![7],
+            ..PlayerData::from_player(&Player::default())
+        };
+        assert!(!deliver_mail(&mut blocked, &from, "hi"));
+        assert!(blocked.mail.is_empty());
+    }
+
+    #[test]
+    fn stops_at_a_full_mailbox() {
+        let mut data = PlayerData::from_player(&Player::default());
+        for _ in 0..MAX_MAIL {
+            assert!(deliver_mail(&mut data, &Player::default(), "hi"));
+        }
+
+        assert!(!deliver_mail(&mut data, &Player::default(), "one more"));
+        assert_eq!(data.mail.len(), MAX_MAIL);
+    }
+
+    #[test]
+    fn mail_survives_saving() {
+        let mut data = PlayerData::from_player(&Player::default());
+        deliver_mail(&mut data, &Player::default(), "hi");
+
+        let json = serde_json::to_string(&data).unwrap();
+        let loaded: PlayerData = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.mail.len(), 1);
+        assert_eq!(loaded.mail[0].text, "hi");
+    }
 }
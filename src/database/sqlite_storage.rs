@@ -0,0 +1,219 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension};
+
+use crate::utils::{
+    self,
+    error::{Error, Result},
+};
+
+use super::{bandb::Punishment, playerdb::PlayerData, storage::Storage};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS players (
+        user_id     INTEGER PRIMARY KEY AUTOINCREMENT,
+        name        TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        pass_hash   TEXT NOT NULL,
+        data        TEXT NOT NULL,
+        created_at  INTEGER NOT NULL,
+        updated_at  INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS bans (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        data        TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS worlds (
+        name        TEXT PRIMARY KEY COLLATE NOCASE,
+        data        BLOB NOT NULL,
+        updated_at  INTEGER NOT NULL
+    );
+";
+
+/// Everything in a single sqlite database. Players are stored as json in `players.data`,
+/// the name, user id and password hash are also kept in their own columns so they can be queried.
+/// Every write is its own transaction, so there is no need for backups like `FileStorage` does.
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    pub fn new(path: &str) -> Result<Self> {
+        if let Some(parent) = Path::new(path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open(path)?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.pragma_update(None, "synchronous", "NORMAL")?;
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self { connection })
+    }
+}
+
+fn unix_now() -> i64 {
+    utils::unix_time() as i64
+}
+
+impl Storage for SqliteStorage {
+    fn player_exists(&self, name: &str) -> Result<bool> {
+        let exists = self
+            .connection
+            .query_row("SELECT 1 FROM players WHERE name = ?1", [name], |_| Ok(()))
+            .optional()?
+            .is_some();
+
+        Ok(exists)
+    }
+
+    fn create_player(&self, data: &PlayerData) -> Result<i32> {
+        let now = unix_now();
+        let transaction = self.connection.unchecked_transaction()?;
+
+        // the id is only known after the insert, so the json gets written twice
+        let result = transaction.execute(
+            "INSERT INTO players (name, pass_hash, data, created_at, updated_at) VALUES (?1, ?2, '{}', ?3, ?3)",
+            params![data.name, data.pass_hash, now],
+        );
+
+        match result {
+            Err(rusqlite::Error::SqliteFailure(e, _))
+                if e.code == ErrorCode::ConstraintViolation =>
+            {
+                return Err(Error::NameAlreadyExists)
+            }
+            result => result?,
+        };
+
+        let user_id = transaction.last_insert_rowid() as i32;
+        let data = PlayerData {
+            user_id,
+            ..data.clone()
+        };
+
+        transaction.execute(
+            "UPDATE players SET data = ?1 WHERE user_id = ?2",
+            params![serde_json::to_string(&data)?, user_id],
+        )?;
+        transaction.commit()?;
+
+        Ok(user_id)
+    }
+
+    fn load_player(&self, name: &str) -> Result<PlayerData> {
+        let content = self
+            .connection
+            .query_row("SELECT data FROM players WHERE name = ?1", [name], |row| {
+                row.get::<_, String>(0)
+            })
+            .optional()?
+            .ok_or(Error::NotFound)?;
+
+        Ok(serde_json::from_str::<PlayerData>(&content)?)
+    }
+
+    fn save_player(&self, data: &PlayerData) -> Result<()> {
+        let updated = self.connection.execute(
+            "UPDATE players SET pass_hash = ?1, data = ?2, updated_at = ?3 WHERE user_id = ?4",
+            params![
+                data.pass_hash,
+                serde_json::to_string(data)?,
+                unix_now(),
+                data.user_id
+            ],
+        )?;
+
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+
+        Ok(())
+    }
+
+    fn list_players(&self) -> Result<Vec<String>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT name FROM players ORDER BY name")?;
+        let names = statement
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(names)
+    }
+
+    fn find_player_name(&self, user_id: i32) -> Result<String> {
+        let name = self
+            .connection
+            .query_row(
+                "SELECT name FROM players WHERE user_id = ?1",
+                [user_id],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .ok_or(Error::NotFound)?;
+
+        Ok(name)
+    }
+
+    fn load_bans(&self) -> Result<Vec<Punishment>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT data FROM bans ORDER BY id")?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        let mut bans = Vec::with_capacity(rows.len());
+        for row in rows.iter() {
+            bans.push(serde_json::from_str::<Punishment>(row)?);
+        }
+
+        Ok(bans)
+    }
+
+    fn save_bans(&self, bans: &[Punishment]) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        transaction.execute("DELETE FROM bans", [])?;
+
+        for ban in bans.iter() {
+            transaction.execute(
+                "INSERT INTO bans (data) VALUES (?1)",
+                [serde_json::to_string(ban)?],
+            )?;
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn world_exists(&self, name: &str) -> Result<bool> {
+        let exists = self
+            .connection
+            .query_row("SELECT 1 FROM worlds WHERE name = ?1", [name], |_| Ok(()))
+            .optional()?
+            .is_some();
+
+        Ok(exists)
+    }
+
+    fn load_world(&self, name: &str) -> Result<Vec<u8>> {
+        self.connection
+            .query_row("SELECT data FROM worlds WHERE name = ?1", [name], |row| {
+                row.get::<_, Vec<u8>>(0)
+            })
+            .optional()?
+            .ok_or(Error::NotFound)
+    }
+
+    fn save_world(&self, name: &str, data: &[u8]) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO worlds (name, data, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(name) DO UPDATE SET data = excluded.data, updated_at = excluded.updated_at",
+            params![name, data, unix_now()],
+        )?;
+
+        Ok(())
+    }
+}
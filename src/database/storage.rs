@@ -0,0 +1,76 @@
+use std::{
+    process,
+    sync::{Mutex, MutexGuard},
+};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::config::CONFIG, utils::error::Result};
+
+use super::{
+    bandb::Punishment, file_storage::FileStorage, playerdb::PlayerData,
+    sqlite_storage::SqliteStorage,
+};
+
+lazy_static! {
+    pub static ref STORAGE: Mutex<Box<dyn Storage>> = match open(CONFIG.storage) {
+        Ok(storage) => Mutex::new(storage),
+        Err(e) => {
+            eprintln!("Error: Could not open {:?} storage: {}", CONFIG.storage, e);
+            process::exit(1);
+        }
+    };
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    File,
+    Sqlite,
+}
+
+/// Where players and worlds are kept. Worlds are stored as the raw bytes produced by `worlddb`.
+pub trait Storage: Send {
+    fn player_exists(&self, name: &str) -> Result<bool>;
+
+    /// Stores a new player and returns its freshly allocated user id.
+    /// Fails with `Error::NameAlreadyExists` if the name is taken.
+    fn create_player(&self, data: &PlayerData) -> Result<i32>;
+    fn load_player(&self, name: &str) -> Result<PlayerData>;
+    fn save_player(&self, data: &PlayerData) -> Result<()>;
+    fn list_players(&self) -> Result<Vec<String>>;
+
+    /// Has to be cheap, it's called from the main loop.
+    fn find_player_name(&self, user_id: i32) -> Result<String>;
+
+    /// Bans and mutes, the whole list is small enough to be loaded and saved at once.
+    fn load_bans(&self) -> Result<Vec<Punishment>>;
+    fn save_bans(&self, bans: &[Punishment]) -> Result<()>;
+
+    fn world_exists(&self, name: &str) -> Result<bool>;
+    fn load_world(&self, name: &str) -> Result<Vec<u8>>;
+    fn save_world(&self, name: &str, data: &[u8]) -> Result<()>;
+
+    /// Older copies of a world, newest first, to fall back to when the current one is corrupted.
+    fn load_world_backups(&self, _name: &str) -> Result<Vec<Vec<u8>>> {
+        Ok(Vec::new())
+    }
+}
+
+pub fn open(kind: StorageKind) -> Result<Box<dyn Storage>> {
+    Ok(match kind {
+        StorageKind::File => Box::new(FileStorage::new(
+            &CONFIG.players_path,
+            &CONFIG.worlds_path,
+            &CONFIG.bans_path,
+            CONFIG.backup_count,
+        )?),
+        StorageKind::Sqlite => Box::new(SqliteStorage::new(&CONFIG.sqlite_path)?),
+    })
+}
+
+/// Don't hold on to the guard, everything in `database` locks it again.
+pub fn storage() -> MutexGuard<'static, Box<dyn Storage>> {
+    STORAGE.lock().unwrap_or_else(|e| e.into_inner())
+}
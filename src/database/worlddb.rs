@@ -1,88 +1,174 @@
 use std::{
     cell::{RefCell, RefMut},
-    fs::{self, File},
-    io::Write,
-    path::Path,
     rc::Rc,
 };
 
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 
 use crate::{
-    consts::{item_type, tile_flags, tileextra_type},
-    item::iteminfo_manager::ITEM_MANAGER,
-    utils::{error::Result, math::Vec2f, mem},
-    world::{tile::Tile, world::World, worldobject::WorldObject, tileextra::TileExtra},
+    consts::{items, tile_flags, tileextra_type},
+    utils::{
+        error::{Error, Result},
+        math::Vec2f,
+        mem,
+    },
+    world::{
+        tile::Tile,
+        tileextra::{Letter, TileExtra},
+        world::World,
+        worldobject::WorldObject,
+    },
 };
 
-const VERSION: u32 = 0;
+use super::storage::storage;
 
-pub fn save_world(mut world: RefMut<World>) -> Result<()> {
-    if !Path::new("data/worlds").exists() {
-        fs::create_dir("data/worlds")?;
-    }
+// Every world file starts with the u32 version of its layout:
+// v0: `World::serialize` output, the same map data the client gets.
+// v1: own layout, stores the main door position and drops client-only fields.
+// v2: signs, locks, seeds, mailboxes, bulletins, display blocks, vending machines and storages.
+// v3: doors have a destination and an id.
+// v4: object and storage counts are a u16, lock access lists keep the names.
+pub const VERSION: u32 = 4;
+
+type Migration = fn(&[u8]) -> Result<Vec<u8>>;
 
-    let mut data = Vec::<u8>::with_capacity((world.width * world.height * 20) as usize);
-    world.serialize(&mut data, None)?;
+// MIGRATIONS[n] turns a v(n) file into a v(n + 1) file, both including the version header.
+const MIGRATIONS: [Migration; VERSION as usize] = [migrate_v0, migrate_v1, migrate_v2, migrate_v3];
 
-    let mut file = File::create(format!("data/worlds/{}.bin", world.name))?;
-    file.write_u32::<LE>(VERSION)?;
-    file.write(&data)?;
+pub fn world_exists(name: &str) -> Result<bool> {
+    storage().world_exists(name)
+}
+
+pub fn save_world(mut world: RefMut<World>) -> Result<()> {
+    let data = write_world(&world)?;
+    storage().save_world(&world.name, &data)?;
+
+    world.dirty = false;
 
     Ok(())
 }
 
 pub fn load_world(name: &str) -> Result<Rc<RefCell<World>>> {
-    if !Path::new("data/worlds").exists() {
-        fs::create_dir("data/worlds")?;
+    let err = match storage()
+        .load_world(name)
+        .and_then(|data| read_world(&data))
+    {
+        Ok(world) => return Ok(world),
+        Err(e) => e,
+    };
+
+    for (i, data) in storage().load_world_backups(name)?.iter().enumerate() {
+        if let Ok(world) = read_world(data) {
+            println!(
+                "World {} could not be loaded ({:?}), using backup #{}.",
+                name,
+                err,
+                i + 1
+            );
+            world.borrow_mut().dirty = true; // so the broken one gets replaced
+            return Ok(world);
+        }
     }
 
+    Err(err)
+}
+
+/// Brings `data` up to the current layout, it is returned as is if it already is.
+pub fn migrate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut header = data;
+    let version = header.read_u32::<LE>()?;
+    if version > VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    let mut data = data.to_vec();
+    for migration in MIGRATIONS[version as usize..].iter() {
+        data = migration(&data)?;
+    }
+
+    Ok(data)
+}
+
+pub fn write_world(world: &World) -> Result<Vec<u8>> {
+    let mut data = Vec::<u8>::with_capacity((world.width * world.height * 10) as usize);
+
+    data.write_u32::<LE>(VERSION)?;
+    data.write_u16::<LE>(world.version)?;
+    data.write_u32::<LE>(world.secret1)?;
+    mem::write_string(&mut data, &world.name)?;
+    data.write_u32::<LE>(world.width)?;
+    data.write_u32::<LE>(world.height)?;
+    data.write_f32::<LE>(world.door_pos.x)?;
+    data.write_f32::<LE>(world.door_pos.y)?;
+    data.write_u32::<LE>(world.tiles.len() as u32)?;
+
+    for tile in world.tiles.iter() {
+        data.write_u16::<LE>(tile.fore)?;
+        data.write_u16::<LE>(tile.back)?;
+        data.write_u16::<LE>(tile.parent)?;
+        data.write_u16::<LE>(tile.flags)?;
+
+        if tile.has_flag(tile_flags::EXTRA_DATA) {
+            write_tile_extra(&mut data, &tile.extra)?;
+        }
+    }
+
+    data.write_u32::<LE>(world.objects.len() as u32)?;
+    data.write_i32::<LE>(world.last_object_id)?;
+
+    for (id, object) in world.objects.iter() {
+        data.write_u32::<LE>(*id)?;
+        data.write_u16::<LE>(object.item_id)?;
+        data.write_f32::<LE>(object.pos.x)?;
+        data.write_f32::<LE>(object.pos.y)?;
+        data.write_u16::<LE>(object.count)?;
+        data.write_u8(object.flags)?;
+    }
+
+    data.write_u32::<LE>(world.weather_base_id)?;
+    data.write_u32::<LE>(world.weather_id)?;
+
+    Ok(data)
+}
+
+pub fn read_world(data: &[u8]) -> Result<Rc<RefCell<World>>> {
+    let data = migrate(data)?;
+    let mut file = &data[4..]; // skip the version, its the current one now
+
     let world = Rc::new(RefCell::new(World::new_none()));
     let reference = &mut world.borrow_mut();
 
-    let mut file = File::open(format!("data/worlds/{}.bin", name))?;
-    let _version = file.read_u32::<LE>()?;
-
     reference.version = file.read_u16::<LE>()?;
     reference.secret1 = file.read_u32::<LE>()?;
     reference.name = mem::read_string(&mut file)?;
     reference.width = file.read_u32::<LE>()?;
     reference.height = file.read_u32::<LE>()?;
+    reference.door_pos = Vec2f {
+        x: file.read_f32::<LE>()?,
+        y: file.read_f32::<LE>()?,
+    };
     let count = file.read_u32::<LE>()?;
 
+    if reference.width == 0 || count != reference.width * reference.height {
+        return Err(Error::CorruptedData);
+    }
+
     reference.tiles = Vec::with_capacity(count as usize);
 
     for i in 0..count {
-        let mut tile = Tile::new(Rc::downgrade(&world), i % reference.width, i / reference.width);
+        let mut tile = Tile::new(
+            Rc::downgrade(&world),
+            i % reference.width,
+            i / reference.width,
+        );
 
         tile.fore = file.read_u16::<LE>()?;
         tile.back = file.read_u16::<LE>()?;
         tile.parent = file.read_u16::<LE>()?;
         tile.flags = file.read_u16::<LE>()?;
 
-        if tile.has_flag(tile_flags::LOCKED) {
-            let _ = file.read_u16::<LE>()?;
-        }
-
         if tile.has_flag(tile_flags::EXTRA_DATA) {
-            let fore = ITEM_MANAGER.get_item_safe(tile.fore as u32)?;
-            let tileextra_type = file.read_u8()?;
-            match tileextra_type {
-                tileextra_type::DOOR => {
-                    let label = mem::read_string(&mut file)?;
-                    let _ = file.read_u8()?;
-
-                    tile.extra = TileExtra::Door { label };
-                    if fore.item_type == item_type::MAIN_DOOR {
-                        reference.door_pos = Vec2f {
-                            x: tile.pos.x as f32 * 32.0,
-                            y: tile.pos.y as f32 * 32.0,
-                        }
-                    }
-                }
-
-                _ => {}
-            }
+            tile.extra = read_tile_extra(&mut file)?;
         }
 
         reference.tiles.push(tile);
@@ -101,7 +187,7 @@ pub fn load_world(name: &str) -> Result<Rc<RefCell<World>>> {
                     y: file.read_f32::<LE>()?,
                 }
             },
-            count: file.read_u8()?,
+            count: file.read_u16::<LE>()?,
             flags: file.read_u8()?,
         };
 
@@ -113,3 +199,894 @@ pub fn load_world(name: &str) -> Result<Rc<RefCell<World>>> {
 
     Ok(world.clone())
 }
+
+fn write_tile_extra(data: &mut Vec<u8>, extra: &TileExtra) -> Result<()> {
+    match extra {
+        TileExtra::None => data.write_u8(0)?,
+
+        TileExtra::Door {
+            label,
+            destination,
+            id,
+        } => {
+            data.write_u8(tileextra_type::DOOR)?;
+            mem::write_string(data, label)?;
+            mem::write_string(data, destination)?;
+            mem::write_string(data, id)?;
+        }
+
+        TileExtra::Sign { label } => {
+            data.write_u8(tileextra_type::SIGN)?;
+            mem::write_string(data, label)?;
+        }
+
+        TileExtra::Lock {
+            flags,
+            owner_uid,
+            owner_name,
+            access,
+        } => {
+            data.write_u8(tileextra_type::LOCK)?;
+            data.write_u8(*flags)?;
+            data.write_i32::<LE>(*owner_uid)?;
+            mem::write_string(data, owner_name)?;
+            data.write_u32::<LE>(access.len() as u32)?;
+
+            for (user_id, name) in access.iter() {
+                data.write_i32::<LE>(*user_id)?;
+                mem::write_string(data, name)?;
+            }
+        }
+
+        TileExtra::Seed {
+            planted_at,
+            fruit_count,
+        } => {
+            data.write_u8(tileextra_type::SEED)?;
+            data.write_u64::<LE>(*planted_at)?;
+            data.write_u8(*fruit_count)?;
+        }
+
+        TileExtra::Mailbox { letters } => {
+            data.write_u8(tileextra_type::MAILBOX)?;
+            write_letters(data, letters)?;
+        }
+
+        TileExtra::Bulletin { letters } => {
+            data.write_u8(tileextra_type::BULLETIN)?;
+            write_letters(data, letters)?;
+        }
+
+        TileExtra::DisplayBlock { item_id } => {
+            data.write_u8(tileextra_type::DISPLAY_BLOCK)?;
+            data.write_u16::<LE>(*item_id)?;
+        }
+
+        TileExtra::VendingMachine {
+            item_id,
+            stock,
+            price,
+        } => {
+            data.write_u8(tileextra_type::VENDING_MACHINE)?;
+            data.write_u16::<LE>(*item_id)?;
+            data.write_u32::<LE>(*stock)?;
+            data.write_i32::<LE>(*price)?;
+        }
+
+        TileExtra::Storage { items } => {
+            data.write_u8(tileextra_type::STORAGE)?;
+            data.write_u32::<LE>(items.len() as u32)?;
+
+            for (id, count) in items.iter() {
+                data.write_u16::<LE>(*id)?;
+                data.write_u16::<LE>(*count)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_letters(data: &mut Vec<u8>, letters: &[Letter]) -> Result<()> {
+    data.write_u32::<LE>(letters.len() as u32)?;
+
+    for letter in letters.iter() {
+        mem::write_string(data, &letter.sender)?;
+        mem::write_string(data, &letter.text)?;
+    }
+
+    Ok(())
+}
+
+fn read_letters(file: &mut &[u8]) -> Result<Vec<Letter>> {
+    let count = file.read_u32::<LE>()?;
+    let mut letters = Vec::new();
+
+    for _ in 0..count {
+        letters.push(Letter {
+            sender: mem::read_string(file)?,
+            text: mem::read_string(file)?,
+        });
+    }
+
+    Ok(letters)
+}
+
+fn read_tile_extra(file: &mut &[u8]) -> Result<TileExtra> {
+    let extra = match file.read_u8()? {
+        0 => TileExtra::None,
+
+        tileextra_type::DOOR => TileExtra::Door {
+            label: mem::read_string(file)?,
+            destination: mem::read_string(file)?,
+            id: mem::read_string(file)?,
+        },
+
+        tileextra_type::SIGN => TileExtra::Sign {
+            label: mem::read_string(file)?,
+        },
+
+        tileextra_type::LOCK => {
+            let flags = file.read_u8()?;
+            let owner_uid = file.read_i32::<LE>()?;
+            let owner_name = mem::read_string(file)?;
+            let count = file.read_u32::<LE>()?;
+
+            let mut access = Vec::new();
+            for _ in 0..count {
+                access.push((file.read_i32::<LE>()?, mem::read_string(file)?));
+            }
+
+            TileExtra::Lock {
+                flags,
+                owner_uid,
+                owner_name,
+                access,
+            }
+        }
+
+        tileextra_type::SEED => TileExtra::Seed {
+            planted_at: file.read_u64::<LE>()?,
+            fruit_count: file.read_u8()?,
+        },
+
+        tileextra_type::MAILBOX => TileExtra::Mailbox {
+            letters: read_letters(file)?,
+        },
+
+        tileextra_type::BULLETIN => TileExtra::Bulletin {
+            letters: read_letters(file)?,
+        },
+
+        tileextra_type::DISPLAY_BLOCK => TileExtra::DisplayBlock {
+            item_id: file.read_u16::<LE>()?,
+        },
+
+        tileextra_type::VENDING_MACHINE => TileExtra::VendingMachine {
+            item_id: file.read_u16::<LE>()?,
+            stock: file.read_u32::<LE>()?,
+            price: file.read_i32::<LE>()?,
+        },
+
+        tileextra_type::STORAGE => {
+            let count = file.read_u32::<LE>()?;
+
+            let mut items = Vec::new();
+            for _ in 0..count {
+                items.push((file.read_u16::<LE>()?, file.read_u16::<LE>()?));
+            }
+
+            TileExtra::Storage { items }
+        }
+
+        _ => return Err(Error::CorruptedData),
+    };
+
+    Ok(extra)
+}
+
+fn migrate_v0(mut old: &[u8]) -> Result<Vec<u8>> {
+    let _version = old.read_u32::<LE>()?;
+
+    let world_version = old.read_u16::<LE>()?;
+    let secret1 = old.read_u32::<LE>()?;
+    let name = mem::read_string(&mut old)?;
+    let width = old.read_u32::<LE>()?;
+    let height = old.read_u32::<LE>()?;
+    let count = old.read_u32::<LE>()?;
+
+    if width == 0 {
+        return Err(Error::CorruptedData);
+    }
+
+    // the door position is only known after going through the tiles
+    let mut door_pos = Vec2f::new(0.0, 0.0);
+    let mut tiles = Vec::<u8>::with_capacity(count as usize * 8);
+
+    for i in 0..count {
+        let fore = old.read_u16::<LE>()?;
+        let back = old.read_u16::<LE>()?;
+        let parent = old.read_u16::<LE>()?;
+        let flags = old.read_u16::<LE>()?;
+
+        tiles.write_u16::<LE>(fore)?;
+        tiles.write_u16::<LE>(back)?;
+        tiles.write_u16::<LE>(parent)?;
+        tiles.write_u16::<LE>(flags)?;
+
+        if flags & tile_flags::LOCKED != 0 {
+            let _ = old.read_u16::<LE>()?; // parent again, the client wants it twice
+        }
+
+        if flags & tile_flags::EXTRA_DATA != 0 {
+            match old.read_u8()? {
+                tileextra_type::DOOR => {
+                    let label = mem::read_string(&mut old)?;
+                    let _ = old.read_u8()?;
+
+                    tiles.write_u8(tileextra_type::DOOR)?;
+                    mem::write_string(&mut tiles, &label)?;
+
+                    // v0 looked this up through items.dat, the generator only ever placed this one
+                    if fore == items::MAIN_DOOR {
+                        door_pos = Vec2f {
+                            x: (i % width) as f32 * 32.0,
+                            y: (i / width) as f32 * 32.0,
+                        };
+                    }
+                }
+
+                _ => return Err(Error::CorruptedData),
+            }
+        }
+    }
+
+    let mut new = Vec::<u8>::with_capacity(tiles.len() + old.len() + 64);
+    new.write_u32::<LE>(1)?;
+    new.write_u16::<LE>(world_version)?;
+    new.write_u32::<LE>(secret1)?;
+    mem::write_string(&mut new, &name)?;
+    new.write_u32::<LE>(width)?;
+    new.write_u32::<LE>(height)?;
+    new.write_f32::<LE>(door_pos.x)?;
+    new.write_f32::<LE>(door_pos.y)?;
+    new.write_u32::<LE>(count)?;
+    new.extend_from_slice(&tiles);
+    new.extend_from_slice(old); // objects and weather are unchanged
+
+    Ok(new)
+}
+
+// v1 could only contain doors, which are stored the same way in v2
+fn migrate_v1(old: &[u8]) -> Result<Vec<u8>> {
+    let mut new = Vec::<u8>::with_capacity(old.len());
+    new.write_u32::<LE>(2)?;
+    new.extend_from_slice(&old[4..]);
+
+    Ok(new)
+}
+
+fn migrate_v2(mut old: &[u8]) -> Result<Vec<u8>> {
+    let _version = old.read_u32::<LE>()?;
+
+    // everything up to the tiles stays the same
+    let header = old;
+    let _world_version = old.read_u16::<LE>()?;
+    let _secret1 = old.read_u32::<LE>()?;
+    let _name = mem::read_string(&mut old)?;
+    let _width = old.read_u32::<LE>()?;
+    let _height = old.read_u32::<LE>()?;
+    let _door_pos = (old.read_f32::<LE>()?, old.read_f32::<LE>()?);
+    let count = old.read_u32::<LE>()?;
+
+    let mut new = Vec::<u8>::with_capacity(header.len() + count as usize * 2);
+    new.write_u32::<LE>(3)?;
+    new.extend_from_slice(&header[..header.len() - old.len()]);
+
+    for _ in 0..count {
+        let tile = old.get(..8).ok_or(Error::CorruptedData)?;
+        let flags = (&tile[6..]).read_u16::<LE>()?;
+        new.extend_from_slice(tile);
+        skip(&mut old, 8)?;
+
+        if flags & tile_flags::EXTRA_DATA != 0 {
+            migrate_extra_v2(&mut old, &mut new)?;
+        }
+    }
+
+    new.extend_from_slice(old); // objects and weather are unchanged
+
+    Ok(new)
+}
+
+// copies one v2 extra as is, doors get an empty destination and id
+fn migrate_extra_v2(old: &mut &[u8], new: &mut Vec<u8>) -> Result<()> {
+    let start = *old;
+    let extra_type = skip_extra_v2(old)?;
+
+    new.extend_from_slice(&start[..start.len() - old.len()]);
+
+    if extra_type == tileextra_type::DOOR {
+        mem::write_string(new, "")?;
+        mem::write_string(new, "")?;
+    }
+
+    Ok(())
+}
+
+fn skip_extra_v2(old: &mut &[u8]) -> Result<u8> {
+    let extra_type = old.first().copied().ok_or(Error::CorruptedData)?;
+
+    match extra_type {
+        0 => skip(old, 1)?,
+
+        tileextra_type::DOOR | tileextra_type::SIGN => {
+            skip(old, 1)?;
+            let _label = mem::read_string(old)?;
+        }
+
+        tileextra_type::LOCK => {
+            skip(old, 1 + 1 + 4)?;
+            let _owner_name = mem::read_string(old)?;
+            let count = old.read_u32::<LE>()?;
+            skip(old, count as usize * 4)?;
+        }
+
+        tileextra_type::SEED => skip(old, 1 + 8 + 1)?,
+
+        tileextra_type::MAILBOX | tileextra_type::BULLETIN => {
+            skip(old, 1)?;
+            for _ in 0..old.read_u32::<LE>()? {
+                let _sender = mem::read_string(old)?;
+                let _text = mem::read_string(old)?;
+            }
+        }
+
+        tileextra_type::DISPLAY_BLOCK => skip(old, 1 + 2)?,
+        tileextra_type::VENDING_MACHINE => skip(old, 1 + 2 + 4 + 4)?,
+
+        tileextra_type::STORAGE => {
+            skip(old, 1)?;
+            let count = old.read_u32::<LE>()?;
+            skip(old, count as usize * 3)?;
+        }
+
+        _ => return Err(Error::CorruptedData),
+    }
+
+    Ok(extra_type)
+}
+
+fn migrate_v3(mut old: &[u8]) -> Result<Vec<u8>> {
+    let _version = old.read_u32::<LE>()?;
+
+    // everything up to the tiles stays the same
+    let header = old;
+    let _world_version = old.read_u16::<LE>()?;
+    let _secret1 = old.read_u32::<LE>()?;
+    let _name = mem::read_string(&mut old)?;
+    let _width = old.read_u32::<LE>()?;
+    let _height = old.read_u32::<LE>()?;
+    let _door_pos = (old.read_f32::<LE>()?, old.read_f32::<LE>()?);
+    let count = old.read_u32::<LE>()?;
+
+    let mut new = Vec::<u8>::with_capacity(header.len() + 64);
+    new.write_u32::<LE>(4)?;
+    new.extend_from_slice(&header[..header.len() - old.len()]);
+
+    for _ in 0..count {
+        let tile = old.get(..8).ok_or(Error::CorruptedData)?;
+        let flags = (&tile[6..]).read_u16::<LE>()?;
+        new.extend_from_slice(tile);
+        skip(&mut old, 8)?;
+
+        if flags & tile_flags::EXTRA_DATA == 0 {
+            continue;
+        }
+
+        match old.first() {
+            Some(&tileextra_type::STORAGE) => {
+                skip(&mut old, 1)?;
+                let items = old.read_u32::<LE>()?;
+                new.write_u8(tileextra_type::STORAGE)?;
+                new.write_u32::<LE>(items)?;
+
+                for _ in 0..items {
+                    new.write_u16::<LE>(old.read_u16::<LE>()?)?;
+                    new.write_u16::<LE>(old.read_u8()? as u16)?;
+                }
+            }
+
+            Some(&tileextra_type::LOCK) => {
+                let start = old;
+                skip(&mut old, 1 + 1 + 4)?;
+                let _owner_name = mem::read_string(&mut old)?;
+                new.extend_from_slice(&start[..start.len() - old.len()]);
+
+                let users = old.read_u32::<LE>()?;
+                new.write_u32::<LE>(users)?;
+
+                // the names weren't kept, the wrench dialog shows the user id instead
+                for _ in 0..users {
+                    new.write_i32::<LE>(old.read_i32::<LE>()?)?;
+                    mem::write_string(&mut new, "")?;
+                }
+            }
+
+            _ => {
+                // the same as in v2, plus the destination and id of doors
+                let start = old;
+                if skip_extra_v2(&mut old)? == tileextra_type::DOOR {
+                    let _destination = mem::read_string(&mut old)?;
+                    let _id = mem::read_string(&mut old)?;
+                }
+
+                new.extend_from_slice(&start[..start.len() - old.len()]);
+            }
+        }
+    }
+
+    let objects_count = old.read_u32::<LE>()?;
+    new.write_u32::<LE>(objects_count)?;
+    new.write_i32::<LE>(old.read_i32::<LE>()?)?; // last object id
+
+    for _ in 0..objects_count {
+        // id, item id and position
+        new.extend_from_slice(old.get(..14).ok_or(Error::CorruptedData)?);
+        skip(&mut old, 14)?;
+
+        new.write_u16::<LE>(old.read_u8()? as u16)?;
+        new.write_u8(old.read_u8()?)?;
+    }
+
+    new.extend_from_slice(old); // weather is unchanged
+
+    Ok(new)
+}
+
+fn skip(data: &mut &[u8], count: usize) -> Result<()> {
+    *data = data.get(count..).ok_or(Error::CorruptedData)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Weak;
+
+    use super::*;
+
+    const WIDTH: u32 = 10;
+    const HEIGHT: u32 = 20;
+
+    fn sample_world() -> World {
+        let mut world = World::new("TEST".to_string(), WIDTH, HEIGHT);
+        world.secret1 = 0x1234;
+        world.last_object_id = 2;
+        world.weather_base_id = 5;
+        world.weather_id = 6;
+
+        for i in 0..WIDTH * HEIGHT {
+            let mut tile = Tile::new(Weak::new(), i % WIDTH, i / WIDTH);
+            if i / WIDTH >= HEIGHT / 2 {
+                tile.fore = items::DIRT;
+                tile.back = items::CAVE_BACKGROUND;
+            }
+
+            world.tiles.push(tile);
+        }
+
+        let door = &mut world.tiles[(3 + 9 * WIDTH) as usize];
+        door.fore = items::MAIN_DOOR;
+        door.flags = tile_flags::EXTRA_DATA;
+        door.extra = TileExtra::main_door();
+        world.door_pos = Vec2f::new(3.0 * 32.0, 9.0 * 32.0);
+
+        let locked = &mut world.tiles[(5 + 12 * WIDTH) as usize];
+        locked.flags = tile_flags::LOCKED | tile_flags::FLIPPED;
+        locked.parent = 42;
+
+        world.objects.insert(
+            1,
+            WorldObject {
+                pos: Vec2f::new(64.0, 96.0),
+                count: 7,
+                flags: 0,
+                item_id: items::DIRT,
+            },
+        );
+
+        world
+    }
+
+    // every kind of extra data, only the current version can store all of them
+    fn full_world() -> World {
+        let mut world = sample_world();
+        let extras = [
+            TileExtra::Door {
+                label: "home".to_string(),
+                destination: "START:1".to_string(),
+                id: "2".to_string(),
+            },
+            TileExtra::Sign {
+                label: "hello".to_string(),
+            },
+            TileExtra::Lock {
+                flags: 1,
+                owner_uid: 7,
+                owner_name: "kevz".to_string(),
+                access: vec![(8, "alt".to_string()), (9, String::new())],
+            },
+            TileExtra::Seed {
+                planted_at: 1_650_000_000,
+                fruit_count: 3,
+            },
+            TileExtra::Mailbox {
+                letters: vec![Letter {
+                    sender: "kevz".to_string(),
+                    text: "hi".to_string(),
+                }],
+            },
+            TileExtra::Bulletin { letters: vec![] },
+            TileExtra::DisplayBlock {
+                item_id: items::DIRT,
+            },
+            TileExtra::VendingMachine {
+                item_id: items::ROCK,
+                stock: 500,
+                price: -5,
+            },
+            TileExtra::Storage {
+                items: vec![(items::DIRT, 200), (items::ROCK, 1)],
+            },
+        ];
+
+        for (i, extra) in extras.into_iter().enumerate() {
+            let tile = &mut world.tiles[i + 2 * WIDTH as usize];
+            tile.fore = items::DOOR; // doesnt matter for the file
+            tile.flags = tile_flags::EXTRA_DATA;
+            tile.extra = extra;
+        }
+
+        world
+    }
+
+    fn assert_same(expected: &World, actual: &World) {
+        assert_eq!(expected.name, actual.name);
+        assert_eq!(expected.version, actual.version);
+        assert_eq!(expected.secret1, actual.secret1);
+        assert_eq!(expected.width, actual.width);
+        assert_eq!(expected.height, actual.height);
+        assert_eq!(expected.door_pos, actual.door_pos);
+        assert_eq!(expected.last_object_id, actual.last_object_id);
+        assert_eq!(expected.objects, actual.objects);
+        assert_eq!(expected.weather_base_id, actual.weather_base_id);
+        assert_eq!(expected.weather_id, actual.weather_id);
+        assert_eq!(expected.tiles.len(), actual.tiles.len());
+
+        for (a, b) in expected.tiles.iter().zip(actual.tiles.iter()) {
+            assert_eq!(a.pos, b.pos);
+            assert_eq!(a.fore, b.fore);
+            assert_eq!(a.back, b.back);
+            assert_eq!(a.parent, b.parent);
+            assert_eq!(a.flags, b.flags);
+            assert_eq!(a.extra, b.extra);
+        }
+    }
+
+    // the layout the server wrote before versioning, frozen here since `World::serialize` moves on
+    fn write_v0(world: &World) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_u32::<LE>(0).unwrap();
+        data.write_u16::<LE>(world.version).unwrap();
+        data.write_u32::<LE>(world.secret1).unwrap();
+        mem::write_string(&mut data, &world.name).unwrap();
+        data.write_u32::<LE>(world.width).unwrap();
+        data.write_u32::<LE>(world.height).unwrap();
+        data.write_u32::<LE>(world.width * world.height).unwrap();
+
+        for tile in world.tiles.iter() {
+            data.write_u16::<LE>(tile.fore).unwrap();
+            data.write_u16::<LE>(tile.back).unwrap();
+            data.write_u16::<LE>(tile.parent).unwrap();
+            data.write_u16::<LE>(tile.flags).unwrap();
+
+            if tile.has_flag(tile_flags::LOCKED) {
+                data.write_u16::<LE>(tile.parent).unwrap();
+            }
+
+            if let TileExtra::Door { label, .. } = &tile.extra {
+                data.write_u8(tileextra_type::DOOR).unwrap();
+                mem::write_string(&mut data, label).unwrap();
+                data.write_u8(0).unwrap();
+            }
+        }
+
+        data.write_u32::<LE>(world.objects.len() as u32).unwrap();
+        data.write_i32::<LE>(world.last_object_id).unwrap();
+
+        for (id, object) in world.objects.iter() {
+            data.write_u32::<LE>(*id).unwrap();
+            data.write_u16::<LE>(object.item_id).unwrap();
+            data.write_f32::<LE>(object.pos.x).unwrap();
+            data.write_f32::<LE>(object.pos.y).unwrap();
+            data.write_u8(object.count as u8).unwrap();
+            data.write_u8(object.flags).unwrap();
+        }
+
+        data.write_u32::<LE>(world.weather_base_id).unwrap();
+        data.write_u32::<LE>(world.weather_id).unwrap();
+        data
+    }
+
+    // frozen v2 layout, doors only had a label
+    fn write_v2(world: &World) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_u32::<LE>(2).unwrap();
+        data.write_u16::<LE>(world.version).unwrap();
+        data.write_u32::<LE>(world.secret1).unwrap();
+        mem::write_string(&mut data, &world.name).unwrap();
+        data.write_u32::<LE>(world.width).unwrap();
+        data.write_u32::<LE>(world.height).unwrap();
+        data.write_f32::<LE>(world.door_pos.x).unwrap();
+        data.write_f32::<LE>(world.door_pos.y).unwrap();
+        data.write_u32::<LE>(world.tiles.len() as u32).unwrap();
+
+        for tile in world.tiles.iter() {
+            data.write_u16::<LE>(tile.fore).unwrap();
+            data.write_u16::<LE>(tile.back).unwrap();
+            data.write_u16::<LE>(tile.parent).unwrap();
+            data.write_u16::<LE>(tile.flags).unwrap();
+
+            if !tile.has_flag(tile_flags::EXTRA_DATA) {
+                continue;
+            }
+
+            match &tile.extra {
+                TileExtra::Door { label, .. } => {
+                    data.write_u8(tileextra_type::DOOR).unwrap();
+                    mem::write_string(&mut data, label).unwrap();
+                }
+
+                // the rest didn't change until v4
+                extra => write_extra_v3(&mut data, extra),
+            }
+        }
+
+        data.write_u32::<LE>(world.objects.len() as u32).unwrap();
+        data.write_i32::<LE>(world.last_object_id).unwrap();
+
+        for (id, object) in world.objects.iter() {
+            data.write_u32::<LE>(*id).unwrap();
+            data.write_u16::<LE>(object.item_id).unwrap();
+            data.write_f32::<LE>(object.pos.x).unwrap();
+            data.write_f32::<LE>(object.pos.y).unwrap();
+            data.write_u8(object.count as u8).unwrap();
+            data.write_u8(object.flags).unwrap();
+        }
+
+        data.write_u32::<LE>(world.weather_base_id).unwrap();
+        data.write_u32::<LE>(world.weather_id).unwrap();
+        data
+    }
+
+    // frozen v3 layout, object and storage counts were a u8
+    fn write_v3(world: &World) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_u32::<LE>(3).unwrap();
+        data.write_u16::<LE>(world.version).unwrap();
+        data.write_u32::<LE>(world.secret1).unwrap();
+        mem::write_string(&mut data, &world.name).unwrap();
+        data.write_u32::<LE>(world.width).unwrap();
+        data.write_u32::<LE>(world.height).unwrap();
+        data.write_f32::<LE>(world.door_pos.x).unwrap();
+        data.write_f32::<LE>(world.door_pos.y).unwrap();
+        data.write_u32::<LE>(world.tiles.len() as u32).unwrap();
+
+        for tile in world.tiles.iter() {
+            data.write_u16::<LE>(tile.fore).unwrap();
+            data.write_u16::<LE>(tile.back).unwrap();
+            data.write_u16::<LE>(tile.parent).unwrap();
+            data.write_u16::<LE>(tile.flags).unwrap();
+
+            if tile.has_flag(tile_flags::EXTRA_DATA) {
+                write_extra_v3(&mut data, &tile.extra);
+            }
+        }
+
+        data.write_u32::<LE>(world.objects.len() as u32).unwrap();
+        data.write_i32::<LE>(world.last_object_id).unwrap();
+
+        for (id, object) in world.objects.iter() {
+            data.write_u32::<LE>(*id).unwrap();
+            data.write_u16::<LE>(object.item_id).unwrap();
+            data.write_f32::<LE>(object.pos.x).unwrap();
+            data.write_f32::<LE>(object.pos.y).unwrap();
+            data.write_u8(object.count as u8).unwrap();
+            data.write_u8(object.flags).unwrap();
+        }
+
+        data.write_u32::<LE>(world.weather_base_id).unwrap();
+        data.write_u32::<LE>(world.weather_id).unwrap();
+        data
+    }
+
+    // extras as v2 and v3 stored them, everything but storages and locks is still the same
+    fn write_extra_v3(data: &mut Vec<u8>, extra: &TileExtra) {
+        match extra {
+            TileExtra::Lock {
+                flags,
+                owner_uid,
+                owner_name,
+                access,
+            } => {
+                data.write_u8(tileextra_type::LOCK).unwrap();
+                data.write_u8(*flags).unwrap();
+                data.write_i32::<LE>(*owner_uid).unwrap();
+                mem::write_string(data, owner_name).unwrap();
+                data.write_u32::<LE>(access.len() as u32).unwrap();
+
+                for (user_id, _) in access.iter() {
+                    data.write_i32::<LE>(*user_id).unwrap();
+                }
+            }
+
+            TileExtra::Storage { items } => {
+                data.write_u8(tileextra_type::STORAGE).unwrap();
+                data.write_u32::<LE>(items.len() as u32).unwrap();
+
+                for (id, count) in items.iter() {
+                    data.write_u16::<LE>(*id).unwrap();
+                    data.write_u8(*count as u8).unwrap();
+                }
+            }
+
+            extra => write_tile_extra(data, extra).unwrap(),
+        }
+    }
+
+    fn write_v1(world: &World) -> Vec<u8> {
+        let mut data = write_v2(world);
+        data[..4].copy_from_slice(&1u32.to_le_bytes());
+        data
+    }
+
+    // what a v2 file of the world can hold
+    fn without_door_targets(mut world: World) -> World {
+        for tile in world.tiles.iter_mut() {
+            if let TileExtra::Door {
+                destination, id, ..
+            } = &mut tile.extra
+            {
+                destination.clear();
+                id.clear();
+            }
+        }
+
+        world
+    }
+
+    // what a v3 file of the world can hold
+    fn without_access_names(mut world: World) -> World {
+        for tile in world.tiles.iter_mut() {
+            if let TileExtra::Lock { access, .. } = &mut tile.extra {
+                for (_, name) in access.iter_mut() {
+                    name.clear();
+                }
+            }
+        }
+
+        world
+    }
+
+    #[test]
+    fn round_trips_current_version() {
+        let world = full_world();
+        let data = write_world(&world).unwrap();
+
+        assert_eq!((&data[..]).read_u32::<LE>().unwrap(), VERSION);
+        assert_same(&world, &read_world(&data).unwrap().borrow());
+    }
+
+    #[test]
+    fn migrates_v0() {
+        let world = sample_world();
+        let loaded = read_world(&write_v0(&world)).unwrap();
+
+        assert_same(&world, &loaded.borrow());
+    }
+
+    #[test]
+    fn migrates_v1() {
+        let world = sample_world();
+        let loaded = read_world(&write_v1(&world)).unwrap();
+
+        assert_same(&world, &loaded.borrow());
+    }
+
+    #[test]
+    fn migrates_v2() {
+        let world = full_world();
+        let loaded = read_world(&write_v2(&world)).unwrap();
+
+        let expected = without_access_names(without_door_targets(world));
+        assert_same(&expected, &loaded.borrow());
+    }
+
+    #[test]
+    fn migrates_v3() {
+        let world = full_world();
+        let loaded = read_world(&write_v3(&world)).unwrap();
+
+        assert_same(&without_access_names(world), &loaded.borrow());
+    }
+
+    #[test]
+    fn round_trips_big_storage_counts() {
+        let mut world = full_world();
+        for tile in world.tiles.iter_mut() {
+            if let TileExtra::Storage { items } = &mut tile.extra {
+                items.push((items::DOOR, 1000));
+            }
+        }
+
+        let data = write_world(&world).unwrap();
+
+        assert_same(&world, &read_world(&data).unwrap().borrow());
+    }
+
+    #[test]
+    fn round_trips_big_object_counts() {
+        let mut world = sample_world();
+        world.objects.get_mut(&1).unwrap().count = 1000;
+        let data = write_world(&world).unwrap();
+
+        assert_same(&world, &read_world(&data).unwrap().borrow());
+    }
+
+    #[test]
+    fn migrated_v0_saves_as_current_version() {
+        let world = sample_world();
+        let loaded = read_world(&write_v0(&world)).unwrap();
+        let data = write_world(&loaded.borrow()).unwrap();
+
+        assert_eq!(migrate(&write_v0(&world)).unwrap(), data);
+        assert_same(&world, &read_world(&data).unwrap().borrow());
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut data = write_world(&sample_world()).unwrap();
+        data[..4].copy_from_slice(&(VERSION + 1).to_le_bytes());
+
+        assert!(matches!(
+            read_world(&data),
+            Err(Error::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let world = sample_world();
+
+        for data in [
+            write_v0(&world),
+            write_v1(&world),
+            write_v2(&world),
+            write_v3(&world),
+            write_world(&world).unwrap(),
+        ] {
+            assert!(read_world(&data[..data.len() - 3]).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_tile_count() {
+        let mut world = sample_world();
+        world.tiles.pop();
+
+        assert!(matches!(
+            read_world(&write_world(&world).unwrap()),
+            Err(Error::CorruptedData)
+        ));
+    }
+}
@@ -1,8 +1,11 @@
 use std::{mem::MaybeUninit, net::Ipv4Addr};
 
-use enet_sys::{_ENetAddress, _ENetHost, enet_host_create, enet_host_destroy, enet_host_service};
+use enet_sys::{
+    _ENetAddress, _ENetHost, enet_host_create, enet_host_destroy, enet_host_flush,
+    enet_host_service,
+};
 
-use super::event::ENetEventType;
+use super::{event::ENetEventType, peer::ENetPeer};
 
 pub struct ENetHost {
     _inner_host: *mut _ENetHost,
@@ -38,6 +41,24 @@ impl ENetHost {
     pub fn online_count(&self) -> usize {
         unsafe { (*self._inner_host).connectedPeers }
     }
+
+    /// Sends out every queued packet without waiting for the next `service` call.
+    pub fn flush(&mut self) {
+        unsafe {
+            enet_host_flush(self._inner_host);
+        }
+    }
+
+    pub fn peers(&self) -> impl Iterator<Item = ENetPeer> + '_ {
+        let (peers, count) = unsafe { ((*self._inner_host).peers, (*self._inner_host).peerCount) };
+        (0..count).map(move |i| ENetPeer::new(unsafe { peers.add(i) }))
+    }
+
+    /// Iterates over the data of every peer that has one set, see `ENetPeer::get_data`.
+    pub fn get_peers_data<'a, T: 'a>(&'a self) -> impl Iterator<Item = &'a mut T> + 'a {
+        self.peers()
+            .filter_map(|peer| unsafe { ((*peer.inner_peer).data as *mut T).as_mut() })
+    }
 }
 
 impl Drop for ENetHost {
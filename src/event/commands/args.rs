@@ -0,0 +1,232 @@
+use crate::{
+    item::{iteminfo::ItemInfo, iteminfo_manager::ITEM_MANAGER},
+    utils::{
+        self,
+        error::{Error, Result},
+    },
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ArgKind {
+    Item,   // item id
+    Player, // player name, not necessarily online
+    Int,
+    Duration, // like 30m or 7d, perm for forever
+    Rest,     // everything left on the line, has to be the last argument
+}
+
+/// How a command wants its arguments, also what `/help` and usage errors are built from.
+pub struct Arg {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub optional: bool,
+}
+
+impl Arg {
+    pub const fn required(name: &'static str, kind: ArgKind) -> Arg {
+        Arg {
+            name,
+            kind,
+            optional: false,
+        }
+    }
+
+    /// Optional arguments can only be followed by other optional arguments.
+    pub const fn optional(name: &'static str, kind: ArgKind) -> Arg {
+        Arg {
+            name,
+            kind,
+            optional: true,
+        }
+    }
+
+    pub fn usage(&self) -> String {
+        if self.optional {
+            format!("[{}]", self.name)
+        } else {
+            format!("<{}>", self.name)
+        }
+    }
+}
+
+enum Value {
+    Item(&'static ItemInfo),
+    Player(String),
+    Int(i64),
+    Duration(Option<u64>),
+    Text(String),
+}
+
+/// Arguments parsed by the kinds the command declared, optional ones that were left out are `None`.
+pub struct Args {
+    values: Vec<Option<Value>>,
+}
+
+impl Args {
+    pub fn parse(specs: &[Arg], text: &str) -> Result<Args> {
+        let mut values = Vec::with_capacity(specs.len());
+        let mut rest = text.trim();
+
+        for spec in specs.iter() {
+            let word = if spec.kind == ArgKind::Rest {
+                std::mem::take(&mut rest)
+            } else {
+                let (word, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                rest = tail.trim_start();
+                word
+            };
+
+            if word.is_empty() {
+                if !spec.optional {
+                    return Err(Error::CommandError(format!("Missing {}.", spec.name)));
+                }
+
+                values.push(None);
+                continue;
+            }
+
+            values.push(Some(Self::parse_value(spec, word)?));
+        }
+
+        if !rest.is_empty() {
+            return Err(Error::CommandError("Too many arguments.".to_string()));
+        }
+
+        Ok(Args { values })
+    }
+
+    fn parse_value(spec: &Arg, word: &str) -> Result<Value> {
+        let invalid = || Error::CommandError(format!("`{}` is not a valid {}.", word, spec.name));
+
+        match spec.kind {
+            ArgKind::Item => {
+                let id = word.parse::<u32>().map_err(|_| invalid())?;
+                match ITEM_MANAGER.get_item_safe(id) {
+                    Ok(item) => Ok(Value::Item(item)),
+                    Err(_) => Err(Error::CommandError(format!("There is no item {}.", id))),
+                }
+            }
+
+            ArgKind::Player => {
+                if word.len() > 18 || word.chars().any(|x| !x.is_ascii_alphanumeric()) {
+                    return Err(invalid());
+                }
+
+                Ok(Value::Player(word.to_string()))
+            }
+
+            ArgKind::Int => word.parse().map(Value::Int).map_err(|_| invalid()),
+
+            ArgKind::Duration => match word {
+                "perm" | "permanent" => Ok(Value::Duration(None)),
+                _ => match utils::parse_duration(word) {
+                    Some(secs) => Ok(Value::Duration(Some(secs))),
+                    None => Err(Error::CommandError(format!(
+                        "`{}` is not a valid {}, use something like 30m, 12h, 7d or perm.",
+                        word, spec.name
+                    ))),
+                },
+            },
+
+            ArgKind::Rest => Ok(Value::Text(word.to_string())),
+        }
+    }
+
+    // the getters return `None` for left out optional arguments, a kind that doesn't match is a bug in the command table
+    pub fn item(&self, index: usize) -> Option<&'static ItemInfo> {
+        match self.values.get(index) {
+            Some(Some(Value::Item(item))) => Some(item),
+            _ => None,
+        }
+    }
+
+    pub fn player(&self, index: usize) -> Option<&str> {
+        match self.values.get(index) {
+            Some(Some(Value::Player(name))) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn int(&self, index: usize) -> Option<i64> {
+        match self.values.get(index) {
+            Some(Some(Value::Int(value))) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Seconds, the inner `None` means forever.
+    pub fn duration(&self, index: usize) -> Option<Option<u64>> {
+        match self.values.get(index) {
+            Some(Some(Value::Duration(secs))) => Some(*secs),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, index: usize) -> Option<&str> {
+        match self.values.get(index) {
+            Some(Some(Value::Text(text))) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPECS: &[Arg] = &[
+        Arg::required("name", ArgKind::Player),
+        Arg::optional("count", ArgKind::Int),
+        Arg::optional("duration", ArgKind::Duration),
+        Arg::optional("reason", ArgKind::Rest),
+    ];
+
+    fn message(result: Result<Args>) -> String {
+        match result {
+            Err(Error::CommandError(message)) => message,
+            _ => panic!("expected a command error"),
+        }
+    }
+
+    #[test]
+    fn parses_every_kind() {
+        let args = Args::parse(SPECS, "  kevz   5 2h being  mean ").unwrap();
+        assert_eq!(args.player(0), Some("kevz"));
+        assert_eq!(args.int(1), Some(5));
+        assert_eq!(args.duration(2), Some(Some(7200)));
+        assert_eq!(args.text(3), Some("being  mean"));
+
+        let args = Args::parse(SPECS, "kevz 5 perm").unwrap();
+        assert_eq!(args.duration(2), Some(None));
+    }
+
+    #[test]
+    fn leaves_out_optional() {
+        let args = Args::parse(SPECS, "kevz").unwrap();
+        assert_eq!(args.player(0), Some("kevz"));
+        assert_eq!(args.int(1), None);
+        assert_eq!(args.duration(2), None);
+        assert_eq!(args.text(3), None);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert_eq!(message(Args::parse(SPECS, "")), "Missing name.");
+        assert_eq!(
+            message(Args::parse(SPECS, "kevz five")),
+            "`five` is not a valid count."
+        );
+        assert_eq!(
+            message(Args::parse(SPECS, "ke|vz")),
+            "`ke|vz` is not a valid name."
+        );
+        assert_eq!(
+            message(Args::parse(&SPECS[..2], "kevz 5 6")),
+            "Too many arguments."
+        );
+        assert_eq!(
+            message(Args::parse(SPECS, "kevz 5 2y")),
+            "`2y` is not a valid duration, use something like 30m, 12h, 7d or perm."
+        );
+    }
+}
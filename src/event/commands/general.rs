@@ -0,0 +1,49 @@
+use crate::{
+    event::event::EventContext,
+    utils::error::{Error, Result},
+};
+
+use super::{find, Args, COMMANDS};
+
+pub fn help(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    match args.text(0) {
+        Some(name) => {
+            let command = find(ctx, name.trim_start_matches('/')).ok_or_else(|| {
+                Error::CommandError(format!("There is no command called {}.", name))
+            })?;
+
+            ctx.player.send_log(&format!(
+                ">> `6{}`` - {}",
+                command.usage(),
+                command.description
+            ));
+
+            if !command.aliases.is_empty() {
+                let aliases: Vec<String> =
+                    command.aliases.iter().map(|x| format!("/{}", x)).collect();
+                ctx.player
+                    .send_log(&format!(">> Also: {}", aliases.join(" ")));
+            }
+        }
+
+        None => {
+            let names: Vec<String> = COMMANDS
+                .iter()
+                .filter(|command| ctx.player.has_permission(command.permission))
+                .map(|command| format!("/{}", command.name))
+                .collect();
+
+            ctx.player
+                .send_log(&format!(">> Commands: {}", names.join(" ")));
+            ctx.player
+                .send_log(">> Type /help <command> to see how to use one.");
+        }
+    }
+
+    Ok(())
+}
+
+pub fn test(ctx: &mut EventContext, _: &Args) -> Result<()> {
+    ctx.player.send_log("Works!");
+    Ok(())
+}
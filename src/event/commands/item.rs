@@ -0,0 +1,35 @@
+use crate::{
+    event::{dialogs::inventory, event::EventContext},
+    utils::error::{Error, Result},
+};
+
+use super::Args;
+
+pub fn give(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let item = args.item(0).ok_or(Error::InvalidPacketError)?;
+    let room = ctx.player.item_room(item.id);
+
+    let count = match args.int(1).unwrap_or(1) {
+        count if count < 1 => {
+            return Err(Error::CommandError("Count must be at least 1.".to_string()))
+        }
+        count if count > room as i64 => {
+            return Err(Error::CommandError(format!(
+                "You only have room for {} more {}.",
+                room, item.name
+            )))
+        }
+        count => count as u16,
+    };
+
+    ctx.player.add_item(item.id, count, true);
+    ctx.player
+        .send_log(&format!(">> `6Given {} {}``!", count, item.name));
+
+    Ok(())
+}
+
+pub fn backpack(ctx: &mut EventContext, _: &Args) -> Result<()> {
+    inventory::send_backpack_dialog(ctx.player);
+    Ok(())
+}
@@ -0,0 +1,227 @@
+mod args;
+mod general;
+mod item;
+mod moderation;
+mod social;
+
+use crate::{
+    consts::permissions,
+    utils::error::{Error, Result},
+};
+
+use super::event::EventContext;
+
+pub use args::{Arg, ArgKind, Args};
+
+pub type CommandHandler = fn(&mut EventContext, &Args) -> Result<()>;
+
+pub struct Command {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub args: &'static [Arg],
+    pub description: &'static str,
+    pub permission: u32,
+    pub handler: CommandHandler,
+}
+
+impl Command {
+    pub fn usage(&self) -> String {
+        let mut usage = format!("/{}", self.name);
+        for arg in self.args.iter() {
+            usage.push(' ');
+            usage.push_str(&arg.usage());
+        }
+
+        usage
+    }
+
+    fn is_called(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+}
+
+// handlers can return `Error::CommandError` to show a message together with the usage
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "help",
+        aliases: &["?"],
+        args: &[Arg::optional("command", ArgKind::Rest)],
+        description: "Shows all commands or how to use one.",
+        permission: permissions::NONE,
+        handler: general::help,
+    },
+    Command {
+        name: "test",
+        aliases: &[],
+        args: &[],
+        description: "Checks if commands work.",
+        permission: permissions::NONE,
+        handler: general::test,
+    },
+    Command {
+        name: "give",
+        aliases: &["item"],
+        args: &[
+            Arg::required("item", ArgKind::Item),
+            Arg::optional("count", ArgKind::Int),
+        ],
+        description: "Gives you an item, one if no count is given.",
+        permission: permissions::GIVE_ITEMS,
+        handler: item::give,
+    },
+    Command {
+        name: "backpack",
+        aliases: &[],
+        args: &[],
+        description: "Buys more backpack slots with gems.",
+        permission: permissions::NONE,
+        handler: item::backpack,
+    },
+    Command {
+        name: "msg",
+        aliases: &["pm", "w"],
+        args: &[
+            Arg::required("name", ArgKind::Player),
+            Arg::required("message", ArgKind::Rest),
+        ],
+        description: "Sends a private message, offline players get it when they log in.",
+        permission: permissions::NONE,
+        handler: social::msg,
+    },
+    Command {
+        name: "r",
+        aliases: &["reply"],
+        args: &[Arg::required("message", ArgKind::Rest)],
+        description: "Answers the last private message you got.",
+        permission: permissions::NONE,
+        handler: social::reply,
+    },
+    Command {
+        name: "block",
+        aliases: &[],
+        args: &[Arg::required("name", ArgKind::Player)],
+        description: "Stops a player from sending you private messages.",
+        permission: permissions::NONE,
+        handler: social::block,
+    },
+    Command {
+        name: "unblock",
+        aliases: &[],
+        args: &[Arg::required("name", ArgKind::Player)],
+        description: "Lets a blocked player message you again.",
+        permission: permissions::NONE,
+        handler: social::unblock,
+    },
+    Command {
+        name: "togglepm",
+        aliases: &[],
+        args: &[],
+        description: "Turns private messages off or back on.",
+        permission: permissions::NONE,
+        handler: social::toggle_messages,
+    },
+    Command {
+        name: "friends",
+        aliases: &[],
+        args: &[],
+        description: "Shows your friends and where they are.",
+        permission: permissions::NONE,
+        handler: social::friends,
+    },
+    Command {
+        name: "unfriend",
+        aliases: &[],
+        args: &[Arg::required("name", ArgKind::Player)],
+        description: "Removes a friend, even when they are offline.",
+        permission: permissions::NONE,
+        handler: social::unfriend,
+    },
+    Command {
+        name: "kick",
+        aliases: &[],
+        args: &[
+            Arg::required("name", ArgKind::Player),
+            Arg::optional("reason", ArgKind::Rest),
+        ],
+        description: "Disconnects a player.",
+        permission: permissions::KICK,
+        handler: moderation::kick,
+    },
+    Command {
+        name: "ban",
+        aliases: &[],
+        args: &[
+            Arg::required("name", ArgKind::Player),
+            Arg::required("duration", ArgKind::Duration),
+            Arg::required("reason", ArgKind::Rest),
+        ],
+        description:
+            "Bans the account, and the device if they are online. Use perm to ban forever.",
+        permission: permissions::BAN,
+        handler: moderation::ban,
+    },
+    Command {
+        name: "unban",
+        aliases: &[],
+        args: &[Arg::required("name", ArgKind::Player)],
+        description: "Lifts a ban early.",
+        permission: permissions::BAN,
+        handler: moderation::unban,
+    },
+    Command {
+        name: "mute",
+        aliases: &[],
+        args: &[
+            Arg::required("name", ArgKind::Player),
+            Arg::required("duration", ArgKind::Duration),
+            Arg::optional("reason", ArgKind::Rest),
+        ],
+        description: "Stops a player from chatting.",
+        permission: permissions::MUTE,
+        handler: moderation::mute,
+    },
+    Command {
+        name: "unmute",
+        aliases: &[],
+        args: &[Arg::required("name", ArgKind::Player)],
+        description: "Lets a muted player chat again.",
+        permission: permissions::MUTE,
+        handler: moderation::unmute,
+    },
+];
+
+/// Commands the player isn't allowed to use don't exist for them.
+pub fn find(ctx: &EventContext, name: &str) -> Option<&'static Command> {
+    let name = name.to_lowercase();
+    COMMANDS
+        .iter()
+        .find(|command| command.is_called(&name) && ctx.player.has_permission(command.permission))
+}
+
+pub fn handle(mut ctx: EventContext) -> Result<()> {
+    ctx.player
+        .send_log(format!("`6{}``", ctx.text_data).as_str());
+
+    let text = ctx.text_data.trim_start_matches('/');
+    let (name, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+
+    let command = match find(&ctx, name) {
+        Some(command) => command,
+        None => {
+            ctx.player
+                .send_log("`4Unknown command!`` Type /help to see all commands.");
+            return Ok(());
+        }
+    };
+
+    match Args::parse(command.args, rest).and_then(|args| (command.handler)(&mut ctx, &args)) {
+        Err(Error::CommandError(message)) => {
+            ctx.player.send_log(&format!("`4Oops!`` {}", message));
+            ctx.player
+                .send_log(&format!(">> Usage: {}", command.usage()));
+            Ok(())
+        }
+
+        result => result,
+    }
+}
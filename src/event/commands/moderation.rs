@@ -0,0 +1,128 @@
+use crate::{
+    database::{
+        bandb::{self, Punishment, PunishmentKind},
+        playerdb,
+    },
+    event::event::EventContext,
+    utils::error::{Error, Result},
+};
+
+use super::Args;
+
+/// Nobody can punish themselves or someone with the same or a higher role.
+fn check_target(ctx: &mut EventContext, name: &str) -> Result<()> {
+    if ctx.player.name.eq_ignore_ascii_case(name) {
+        return Err(Error::CommandError(
+            "You can't do that to yourself.".to_string(),
+        ));
+    }
+
+    let role = match ctx.sessions.get_by_name(name, Some(ctx.player)) {
+        Some(player) => player.role,
+        None => playerdb::find_role(name)
+            .map_err(|_| Error::CommandError(format!("There is no player named {}.", name)))?,
+    };
+
+    if role >= ctx.player.role {
+        return Err(Error::CommandError(format!(
+            "You can't do that to a {}.",
+            role.name()
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn kick(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let name = args.player(0).ok_or(Error::InvalidPacketError)?;
+    check_target(ctx, name)?;
+
+    let target = ctx
+        .sessions
+        .get_by_name(name, Some(ctx.player))
+        .ok_or_else(|| Error::CommandError(format!("{} isn't online.", name)))?;
+
+    match args.text(1) {
+        Some(reason) => target.send_log(&format!("`4You have been kicked``. Reason: {}", reason)),
+        None => target.send_log("`4You have been kicked``."),
+    }
+    target.disconnect();
+
+    ctx.player.send_log(&format!(">> Kicked {}.", target.name));
+    Ok(())
+}
+
+pub fn ban(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let name = args.player(0).ok_or(Error::InvalidPacketError)?;
+    let secs = args.duration(1).ok_or(Error::InvalidPacketError)?;
+    let reason = args.text(2).ok_or(Error::InvalidPacketError)?;
+    check_target(ctx, name)?;
+
+    let mut ban = Punishment::new(PunishmentKind::Ban, name, &ctx.player.name, reason, secs);
+
+    // also ban the device, so they can't just make a new account
+    let target = ctx.sessions.get_by_name(name, Some(ctx.player));
+    if let Some(target) = target.as_ref() {
+        ban.name = target.name.to_owned();
+        ban.mac = target.mac_address.to_owned();
+        ban.rid = target.rid.to_owned();
+    }
+
+    let remaining = ban.remaining();
+    bandb::add(ban)?;
+
+    if let Some(target) = target {
+        target.send_log(&format!(
+            "`4You have been banned`` for {}. Reason: {}",
+            remaining, reason
+        ));
+        target.disconnect();
+    }
+
+    ctx.player
+        .send_log(&format!(">> Banned {} for {}.", name, remaining));
+    Ok(())
+}
+
+pub fn unban(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let name = args.player(0).ok_or(Error::InvalidPacketError)?;
+    if !bandb::remove(PunishmentKind::Ban, name)? {
+        return Err(Error::CommandError(format!("{} isn't banned.", name)));
+    }
+
+    ctx.player.send_log(&format!(">> Unbanned {}.", name));
+    Ok(())
+}
+
+pub fn mute(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let name = args.player(0).ok_or(Error::InvalidPacketError)?;
+    let secs = args.duration(1).ok_or(Error::InvalidPacketError)?;
+    let reason = args.text(2).unwrap_or("");
+    check_target(ctx, name)?;
+
+    let mute = Punishment::new(PunishmentKind::Mute, name, &ctx.player.name, reason, secs);
+    let remaining = mute.remaining();
+    bandb::add(mute)?;
+
+    if let Some(target) = ctx.sessions.get_by_name(name, Some(ctx.player)) {
+        target.send_log(&format!("`4You have been muted`` for {}.", remaining));
+    }
+
+    ctx.player
+        .send_log(&format!(">> Muted {} for {}.", name, remaining));
+    Ok(())
+}
+
+pub fn unmute(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let name = args.player(0).ok_or(Error::InvalidPacketError)?;
+    if !bandb::remove(PunishmentKind::Mute, name)? {
+        return Err(Error::CommandError(format!("{} isn't muted.", name)));
+    }
+
+    if let Some(target) = ctx.sessions.get_by_name(name, Some(ctx.player)) {
+        target.send_log("`2You can talk again.``");
+    }
+
+    ctx.player.send_log(&format!(">> Unmuted {}.", name));
+    Ok(())
+}
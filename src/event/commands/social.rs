@@ -0,0 +1,156 @@
+use crate::{
+    database::{
+        bandb::{self, PunishmentKind},
+        playerdb,
+    },
+    event::{dialogs::friends as friends_dialog, event::EventContext},
+    utils::error::{Error, Result},
+};
+
+use super::Args;
+
+const MAX_MESSAGE_LENGTH: usize = 120;
+
+// online players get it right away, everyone else when they log in
+fn send_message(ctx: &mut EventContext, name: &str, text: &str) -> Result<()> {
+    if bandb::find(PunishmentKind::Mute, ctx.player).is_some() {
+        return Err(Error::CommandError(
+            "You can't send messages while muted.".to_string(),
+        ));
+    }
+
+    if text.len() > MAX_MESSAGE_LENGTH {
+        return Err(Error::CommandError(format!(
+            "Messages can be {} characters long at most.",
+            MAX_MESSAGE_LENGTH
+        )));
+    }
+
+    if ctx.player.name.eq_ignore_ascii_case(name) {
+        return Err(Error::CommandError(
+            "You can't message yourself.".to_string(),
+        ));
+    }
+
+    let refused = || Error::CommandError(format!("{} isn't accepting messages.", name));
+
+    match ctx.sessions.get_by_name(name, Some(ctx.player)) {
+        Some(target) => {
+            if !target.accepts_messages_from(ctx.player.user_id) {
+                return Err(refused());
+            }
+
+            target.last_message_from = ctx.player.name.to_owned();
+            target.send_private_message(&ctx.player.display(), &ctx.player.current_world, text);
+            ctx.player
+                .send_log(&format!("`6>> (Sent to {}`6)``", target.display()));
+        }
+
+        None => match playerdb::send_mail(name, ctx.player, text) {
+            Ok(true) => ctx.player.send_log(&format!(
+                "`6>> {} is offline, they will get your message when they log in.``",
+                name
+            )),
+            Ok(false) => return Err(refused()),
+            Err(Error::NotFound) => {
+                return Err(Error::CommandError(format!(
+                    "There is no player named {}.",
+                    name
+                )))
+            }
+            Err(e) => return Err(e),
+        },
+    }
+
+    Ok(())
+}
+
+pub fn msg(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let name = args.player(0).ok_or(Error::InvalidPacketError)?;
+    let text = args.text(1).ok_or(Error::InvalidPacketError)?;
+    send_message(ctx, name, text)
+}
+
+pub fn reply(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let text = args.text(0).ok_or(Error::InvalidPacketError)?;
+    if ctx.player.last_message_from.is_empty() {
+        return Err(Error::CommandError("Nobody messaged you yet.".to_string()));
+    }
+
+    let name = ctx.player.last_message_from.to_owned();
+    send_message(ctx, &name, text)
+}
+
+pub fn block(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let name = args.player(0).ok_or(Error::InvalidPacketError)?;
+    let user_id = match ctx.sessions.find_user_id(name) {
+        Some(user_id) => user_id,
+        None => playerdb::find_user_id(name)
+            .map_err(|_| Error::CommandError(format!("There is no player named {}.", name)))?,
+    };
+
+    if user_id == ctx.player.user_id || ctx.player.blocked.contains(&user_id) {
+        return Err(Error::CommandError(format!("{} is already blocked.", name)));
+    }
+
+    ctx.player.blocked.push(user_id);
+    ctx.player.dirty = true;
+    ctx.player.send_log(&format!(
+        ">> Blocked {}, they can't message you anymore.",
+        name
+    ));
+
+    Ok(())
+}
+
+pub fn unblock(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let name = args.player(0).ok_or(Error::InvalidPacketError)?;
+    let user_id = match ctx.sessions.find_user_id(name) {
+        Some(user_id) => Some(user_id),
+        None => playerdb::find_user_id(name).ok(),
+    };
+
+    match user_id {
+        Some(user_id) if ctx.player.blocked.contains(&user_id) => {
+            ctx.player.blocked.retain(|x| *x != user_id);
+            ctx.player.dirty = true;
+            ctx.player.send_log(&format!(">> Unblocked {}.", name));
+            Ok(())
+        }
+
+        _ => Err(Error::CommandError(format!("{} isn't blocked.", name))),
+    }
+}
+
+pub fn toggle_messages(ctx: &mut EventContext, _: &Args) -> Result<()> {
+    ctx.player.pm_muted = !ctx.player.pm_muted;
+    ctx.player.dirty = true;
+
+    if ctx.player.pm_muted {
+        ctx.player.send_log(">> Private messages are now `4off``.");
+    } else {
+        ctx.player.send_log(">> Private messages are now `2on``.");
+    }
+
+    Ok(())
+}
+
+pub fn friends(ctx: &mut EventContext, _: &Args) -> Result<()> {
+    friends_dialog::send_friends_dialog(ctx);
+    Ok(())
+}
+
+pub fn unfriend(ctx: &mut EventContext, args: &Args) -> Result<()> {
+    let name = args.player(0).ok_or(Error::InvalidPacketError)?;
+    let user_id = match ctx
+        .player
+        .friends
+        .iter()
+        .find(|x| x.name.eq_ignore_ascii_case(name))
+    {
+        Some(friend) => friend.user_id,
+        None => return Err(Error::CommandError(format!("{} isn't your friend.", name))),
+    };
+
+    friends_dialog::remove(ctx, user_id)
+}
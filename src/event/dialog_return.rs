@@ -1,83 +1,162 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
-use crate::{
-    database::playerdb,
-    utils::error::{Error, Result},
-    utils::variant_function::VariantFunction::*,
+use crate::utils::{
+    dialog,
+    error::{Error, Result},
 };
 
-use super::event::EventContext;
-
-pub fn handle(ctx: EventContext, map: HashMap<&str, &str>) -> Result<()> {
-    match map.get("dialog_name") {
-        Some(&dialog_name) => match dialog_name {
-            "growid" => {
-                let name = map.get("name");
-                let pass = map.get("pass");
-                let verify_pass = map.get("verify_pass");
-
-                if let Some(name) = name {
-                    if let Some(pass) = pass {
-                        if let Some(verify_pass) = verify_pass {
-                            if name.len() < 3 || name.len() > 12 {
-                                ctx.player
-                                    .send_log("`4Name must between 3 to 12 characters.``");
-                                return Err(Error::Disconnected);
-                            }
-
-                            if pass.len() < 8 || pass.len() > 18 {
-                                ctx.player
-                                    .send_log("`Password must between 8 to 18 characters.``");
-                                return Err(Error::Disconnected);
-                            }
-
-                            if name.chars().any(|x| !x.is_ascii_alphanumeric()) {
-                                ctx.player
-                                    .send_log("`4Name cannot contains special characters.``");
-                                return Err(Error::Disconnected);
-                            }
-
-                            if pass != verify_pass {
-                                ctx.player.send_log("`4Password doesn't match.``");
-                                return Err(Error::Disconnected);
-                            }
-
-                            ctx.player.name = name.to_string();
-                            ctx.player.pass = pass.to_string();
-
-                            match playerdb::create_player_database(ctx.player) {
-                                Ok(_) => {
-                                    ctx.player.send_log("`6Your account has been created! Please wait while entering the server.``");
-                                    ctx.player.send_varfn(SetHasGrowID(true, name, pass));
-                                    ctx.player.send_varfn_v(OnSuperMain, -1, 869);
-                                    ctx.player.authenticated = true;
-                                }
-
-                                Err(e) => {
-                                    match e {
-                                        Error::NameAlreadyExists => {
-                                            ctx.player.send_log("`4Name already exists!``");
-                                        }
-
-                                        _ => ctx.player.send_log(&format!(
-                                            "`4Unknown error: {}!``",
-                                            e.to_string()
-                                        )),
-                                    }
-
-                                    return Err(e);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+use super::{
+    dialogs::{account, friends, inventory, player, tile, trade},
+    event::EventContext,
+};
 
-            _ => {}
-        },
+pub type DialogHandler = fn(&mut EventContext, &DialogReturn) -> Result<()>;
+
+struct DialogRoute {
+    name: &'static str,
+    handler: DialogHandler,
+    authenticated: bool, // only logged in players can send it
+}
+
+// every dialog the server expects an answer from, keyed by the name given to `end_dialog`
+const ROUTES: &[DialogRoute] = &[
+    DialogRoute {
+        name: "growid",
+        handler: account::on_growid_return,
+        authenticated: false,
+    },
+    DialogRoute {
+        name: "drop_item",
+        handler: inventory::on_drop_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "trash_item",
+        handler: inventory::on_trash_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "trash_confirm",
+        handler: inventory::on_trash_confirm_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "backpack",
+        handler: inventory::on_backpack_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "sign_edit",
+        handler: tile::on_sign_edit_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "door_edit",
+        handler: tile::on_door_edit_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "lock_edit",
+        handler: tile::on_lock_edit_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "player_wrench",
+        handler: player::on_wrench_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "friend_request",
+        handler: friends::on_request_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "trade_request",
+        handler: trade::on_request_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "trade",
+        handler: trade::on_trade_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "trade_offer",
+        handler: trade::on_offer_return,
+        authenticated: true,
+    },
+    DialogRoute {
+        name: "trade_confirm",
+        handler: trade::on_confirm_return,
+        authenticated: true,
+    },
+];
+
+/// The fields of a `dialog_return`, inputs and checkboxes by their name plus everything from `embed_data`.
+pub struct DialogReturn<'a> {
+    pub name: &'a str,
+    fields: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> DialogReturn<'a> {
+    pub fn new(fields: HashMap<&'a str, &'a str>) -> Result<Self> {
+        let name = *fields.get("dialog_name").ok_or(Error::InvalidPacketError)?;
+        Ok(Self { name, fields })
+    }
+
+    /// Fields the client always sends, like embedded data. Missing ones mean a forged packet.
+    pub fn get(&self, key: &str) -> Result<&'a str> {
+        self.fields
+            .get(key)
+            .copied()
+            .ok_or(Error::InvalidPacketError)
+    }
+
+    pub fn parse<T: FromStr>(&self, key: &str) -> Result<T> {
+        self.get(key)?
+            .trim()
+            .parse()
+            .map_err(|_| Error::InvalidPacketError)
+    }
 
-        None => return Err(Error::InvalidPacketError),
+    /// What the player typed in, cleaned up and cut to `max_len` characters. Empty if missing.
+    pub fn text(&self, key: &str, max_len: usize) -> String {
+        dialog::clean(self.fields.get(key).unwrap_or(&""))
+            .chars()
+            .take(max_len)
+            .collect::<String>()
+            .trim()
+            .to_string()
     }
 
-    Ok(())
+    /// Name of the button that closed the dialog, empty for the ok and cancel buttons.
+    pub fn button(&self) -> &'a str {
+        self.fields.get("buttonClicked").copied().unwrap_or("")
+    }
+
+    pub fn checkbox(&self, key: &str) -> bool {
+        self.fields.get(key) == Some(&"1")
+    }
+
+    /// Set by dialogs opened by wrenching a tile.
+    pub fn tile_pos(&self) -> Result<(u32, u32)> {
+        Ok((self.parse("tilex")?, self.parse("tiley")?))
+    }
+}
+
+pub fn handle(mut ctx: EventContext, fields: HashMap<&str, &str>) -> Result<()> {
+    let dialog = DialogReturn::new(fields)?;
+
+    match ROUTES.iter().find(|route| route.name == dialog.name) {
+        Some(route) => {
+            if route.authenticated && !ctx.player.authenticated {
+                return Err(Error::InvalidPacketError);
+            }
+
+            (route.handler)(&mut ctx, &dialog)
+        }
+
+        // dialogs that are only there to be read
+        None => Ok(()),
+    }
 }
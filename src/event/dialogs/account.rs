@@ -0,0 +1,105 @@
+use crate::{
+    database::playerdb,
+    event::{dialog_return::DialogReturn, event::EventContext},
+    player::player::Player,
+    utils::{
+        dialog::{Dialog, Size},
+        error::{Error, Result},
+        variant_function::VariantFunction::*,
+    },
+};
+
+pub fn send_growid_dialog(player: &mut Player) {
+    player.send_dialog(
+        Dialog::new()
+            .add_label_with_icon(Size::Big, "GrowID Creation", 204)
+            .add_spacer(Size::Small)
+            .add_button("create", "`6Create!``")
+            .add_text_input("name", "GrowID:", "", 12)
+            .add_smalltext("GrowID should be between 3 and 12 characters long.")
+            .add_password_input("pass", "Password:", 18)
+            .add_password_input("verify_pass", "Verify Password:", 18)
+            .add_smalltext("Password should be between 8 and 18 characters long.")
+            .add_spacer(Size::Small)
+            .end_dialog("growid", "", ""),
+    );
+}
+
+pub fn send_welcome_dialog(player: &mut Player) {
+    player.send_dialog(
+        Dialog::new()
+            .add_label_with_icon(Size::Big, "Grow Rust", 18)
+            .add_spacer(Size::Small)
+            .add_textbox("Hello player! You are in `6Grow Rust`` server!")
+            .add_textbox("This server was made by `6kevz#2211``.")
+            .add_textbox("What makes this server unique is that it was built with the `6Rust`` programming language.")
+            .add_textbox("This project is opensourced at my (`6kevz#2211``) github.")
+            .add_textbox("Anyways, if you are going to use this server as base server, don't forget to credit me! :D")
+            .add_spacer(Size::Small)
+            .add_url_button("`1Kevz's Github``", "https://github.com/zKevz", "Visit `1Kevz's Github``?")
+            .add_url_button("`1Project's Repository``", "https://github.com/zKevz/GrowRust", "Visit this project repository in github?")
+            .add_spacer(Size::Small)
+            .end_dialog("lol", "", "Close")
+            .add_quick_exit(),
+    );
+}
+
+pub fn on_growid_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let name = dialog.get("name")?;
+    let pass = dialog.get("pass")?;
+    let verify_pass = dialog.get("verify_pass")?;
+
+    if name.len() < 3 || name.len() > 12 {
+        ctx.player
+            .send_log("`4Name must between 3 to 12 characters.``");
+        return Err(Error::Disconnected);
+    }
+
+    if pass.len() < 8 || pass.len() > 18 {
+        ctx.player
+            .send_log("`Password must between 8 to 18 characters.``");
+        return Err(Error::Disconnected);
+    }
+
+    if name.chars().any(|x| !x.is_ascii_alphanumeric()) {
+        ctx.player
+            .send_log("`4Name cannot contains special characters.``");
+        return Err(Error::Disconnected);
+    }
+
+    if pass != verify_pass {
+        ctx.player.send_log("`4Password doesn't match.``");
+        return Err(Error::Disconnected);
+    }
+
+    ctx.player.name = name.to_string();
+    ctx.player.pass = pass.to_string();
+
+    match playerdb::create_player_database(ctx.player) {
+        Ok(_) => {
+            ctx.player.send_log(
+                "`6Your account has been created! Please wait while entering the server.``",
+            );
+            ctx.player.send_varfn(SetHasGrowID(true, name, pass));
+            ctx.player.send_varfn_v(OnSuperMain, -1, 869);
+            ctx.sessions.register(ctx.player);
+            ctx.player.authenticated = true;
+        }
+
+        Err(e) => {
+            match e {
+                Error::NameAlreadyExists => {
+                    ctx.player.send_log("`4Name already exists!``");
+                }
+
+                _ => ctx
+                    .player
+                    .send_log(&format!("`4Unknown error: {}!``", e.to_string())),
+            }
+
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use crate::{
+    consts::items,
+    database::playerdb,
+    event::{dialog_return::DialogReturn, event::EventContext},
+    player::friend::{Friend, MAX_FRIENDS},
+    utils::{
+        dialog::{Dialog, Size},
+        error::{Error, Result},
+    },
+};
+
+/// Friends with their online status and world, for the friends button and `/friends`.
+pub fn send_friends_dialog(ctx: &mut EventContext) {
+    let mut dialog = Dialog::new().add_label_with_icon(
+        Size::Big,
+        &format!("Friends ({}/{})", ctx.player.friends.len(), MAX_FRIENDS),
+        items::FRIENDS_ENTRANCE,
+    );
+
+    if ctx.player.friends.is_empty() {
+        dialog = dialog.add_textbox("You don't have any friends yet, wrench someone to add them!");
+    }
+
+    for friend in ctx.player.friends.iter() {
+        let status = match ctx.sessions.get(friend.user_id, Some(ctx.player)) {
+            Some(online) if online.current_world == "EXIT" => "`2online``".to_string(),
+            Some(online) => format!("`2online`` in `w{}``", online.current_world),
+            None => "`4offline``".to_string(),
+        };
+
+        dialog = dialog.add_label(Size::Small, &format!("`w{}`` - {}", friend.name, status));
+    }
+
+    ctx.player.send_dialog(
+        dialog
+            .add_spacer(Size::Small)
+            .add_smalltext("Wrench a friend to remove them, or use /unfriend for offline ones.")
+            .end_dialog("friends", "", "Close"),
+    );
+}
+
+pub fn send_request(ctx: &mut EventContext, user_id: i32) -> Result<()> {
+    if ctx.player.is_friend(user_id) {
+        return Ok(());
+    }
+
+    if ctx.player.friends.len() >= MAX_FRIENDS {
+        ctx.player.send_log(&format!(
+            "`4Oops!`` You can't have more than {} friends.",
+            MAX_FRIENDS
+        ));
+        return Ok(());
+    }
+
+    // they asked first, so this is just accepting
+    if ctx
+        .player
+        .friend_requests
+        .iter()
+        .any(|x| x.user_id == user_id)
+    {
+        return accept(ctx, user_id);
+    }
+
+    let target = match ctx.sessions.get(user_id, Some(ctx.player)) {
+        Some(target) => target,
+        None => {
+            ctx.player
+                .send_log("`4Oops!`` That player isn't online anymore.");
+            return Ok(());
+        }
+    };
+
+    // blocked players don't find out
+    if !target.blocked.contains(&ctx.player.user_id)
+        && !target
+            .friend_requests
+            .iter()
+            .any(|x| x.user_id == ctx.player.user_id)
+    {
+        target.friend_requests.push(Friend {
+            user_id: ctx.player.user_id,
+            name: ctx.player.name.to_owned(),
+        });
+        target.send_dialog(
+            Dialog::new()
+                .add_label_with_icon(Size::Big, "Friend Request", items::FRIENDS_ENTRANCE)
+                .add_textbox(&format!(
+                    "{} wants to be your friend.",
+                    ctx.player.display()
+                ))
+                .add_button("accept", "`2Accept``")
+                .add_button("decline", "`4Decline``")
+                .embed_data("userID", ctx.player.user_id)
+                .end_dialog("friend_request", "", "")
+                .add_quick_exit(),
+        );
+    }
+
+    ctx.player.send_log(&format!(
+        "`5Sent a friend request to {}``.",
+        target.display()
+    ));
+
+    Ok(())
+}
+
+pub fn on_request_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let user_id: i32 = dialog.parse("userID")?;
+
+    // can't accept requests that were never sent
+    if !ctx
+        .player
+        .friend_requests
+        .iter()
+        .any(|x| x.user_id == user_id)
+    {
+        return Ok(());
+    }
+
+    match dialog.button() {
+        "accept" => accept(ctx, user_id),
+        "decline" | "" => {
+            ctx.player.friend_requests.retain(|x| x.user_id != user_id);
+            Ok(())
+        }
+        _ => Err(Error::InvalidPacketError),
+    }
+}
+
+fn accept(ctx: &mut EventContext, user_id: i32) -> Result<()> {
+    let request = match ctx
+        .player
+        .friend_requests
+        .iter()
+        .position(|x| x.user_id == user_id)
+    {
+        Some(i) => ctx.player.friend_requests.remove(i),
+        None => return Ok(()),
+    };
+
+    if ctx.player.friends.len() >= MAX_FRIENDS {
+        ctx.player.send_log(&format!(
+            "`4Oops!`` You can't have more than {} friends.",
+            MAX_FRIENDS
+        ));
+        return Ok(());
+    }
+
+    let (name, display) = match ctx.sessions.get(user_id, Some(ctx.player)) {
+        Some(friend) => {
+            if friend.friends.len() >= MAX_FRIENDS {
+                ctx.player.send_log(&format!(
+                    "`4Oops!`` {} can't have any more friends.",
+                    friend.display()
+                ));
+                return Ok(());
+            }
+
+            friend.add_friend(ctx.player.user_id, &ctx.player.name);
+            friend.send_log(&format!("`3{} is now your friend!``", ctx.player.display()));
+
+            (friend.name.to_owned(), friend.display())
+        }
+
+        // logged off in the meantime
+        None => {
+            let (friend_id, friend_name) = (ctx.player.user_id, ctx.player.name.to_owned());
+            let saved = playerdb::update_offline_player(&request.name, |data| {
+                if !data.friends.iter().any(|x| x.user_id == friend_id) {
+                    data.friends.push(Friend {
+                        user_id: friend_id,
+                        name: friend_name,
+                    });
+                }
+            });
+
+            if let Err(e) = saved {
+                println!("Failed to save player {}! Error: {:?}", request.name, e);
+                ctx.player.send_log(&format!(
+                    "`4Oops!`` Couldn't add {}, send them a request once they're back.",
+                    request.name
+                ));
+                return Ok(());
+            }
+
+            (request.name.to_owned(), format!("`w{}``", request.name))
+        }
+    };
+
+    ctx.player.add_friend(user_id, &name);
+    ctx.player
+        .send_log(&format!("`3{} is now your friend!``", display));
+
+    Ok(())
+}
+
+/// Ends the friendship on both sides.
+pub fn remove(ctx: &mut EventContext, user_id: i32) -> Result<()> {
+    let name = match ctx.player.friends.iter().find(|x| x.user_id == user_id) {
+        Some(friend) => friend.name.to_owned(),
+        None => return Ok(()),
+    };
+
+    match ctx.sessions.get(user_id, Some(ctx.player)) {
+        Some(friend) => friend.remove_friend(ctx.player.user_id),
+        None => {
+            let friend_id = ctx.player.user_id;
+            playerdb::update_offline_player(&name, |data| {
+                data.friends.retain(|x| x.user_id != friend_id)
+            })?;
+        }
+    }
+
+    ctx.player.remove_friend(user_id);
+    ctx.player
+        .send_log(&format!("`5{} is no longer your friend.``", name));
+
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use crate::{
+    consts::{items, packet_flags},
+    event::{dialog_return::DialogReturn, event::EventContext},
+    item::iteminfo_manager::ITEM_MANAGER,
+    player::player::{Player, DEFAULT_ITEMS_SLOTS},
+    utils::{
+        dialog::{Dialog, Size},
+        error::{Error, Result},
+        math::Vec2f,
+    },
+};
+
+const DROP_DISTANCE: f32 = 32.0;
+
+const BACKPACK_UPGRADE_SLOTS: u32 = 10;
+const MAX_ITEMS_SLOTS: u32 = 396;
+
+pub fn send_drop_dialog(player: &mut Player, id: u16) -> Result<()> {
+    let item = ITEM_MANAGER.get_item_safe(id as u32)?;
+    let count = player.get_item_count(id);
+    if count == 0 {
+        return Err(Error::InvalidPacketError);
+    }
+
+    if !item.is_droppable() {
+        player.send_log("`4Oops!`` You can't drop that.");
+        return Ok(());
+    }
+
+    player.send_dialog(
+        Dialog::new()
+            .add_label_with_icon(Size::Big, &format!("`wDrop {}``", item.name), id)
+            .add_textbox("How many to drop?")
+            .add_text_input("count", "", &count.to_string(), 5)
+            .embed_data("itemID", id)
+            .end_dialog("drop_item", "Cancel", "OK"),
+    );
+
+    Ok(())
+}
+
+pub fn on_drop_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let id: u16 = dialog.parse("itemID")?;
+    let item = ITEM_MANAGER.get_item_safe(id as u32)?;
+
+    let count = match dialog.parse::<u16>("count") {
+        Ok(count) if count > 0 && count <= ctx.player.get_item_count(id) => count,
+        _ => {
+            ctx.player.send_log("`4Oops!`` You don't have that many.");
+            return Ok(());
+        }
+    };
+
+    if !item.is_droppable() {
+        return Err(Error::InvalidPacketError);
+    }
+
+    let world = ctx.player.get_world(ctx.world_manager)?;
+    let mut world = world.borrow_mut();
+
+    // in front of the player
+    let offset = if ctx.player.char_flags & packet_flags::FACINGLEFT != 0 {
+        -DROP_DISTANCE
+    } else {
+        DROP_DISTANCE
+    };
+
+    let max_x = (world.width * 32 - 32) as f32;
+    let pos = Vec2f::new(
+        (ctx.player.pos.x + offset).clamp(0.0, max_x),
+        ctx.player.pos.y,
+    );
+
+    ctx.player.remove_item(id, count, true)?;
+    world.drop_object(id, count, pos);
+    world.send_all();
+
+    Ok(())
+}
+
+pub fn send_trash_dialog(player: &mut Player, id: u16) -> Result<()> {
+    let item = ITEM_MANAGER.get_item_safe(id as u32)?;
+    let count = player.get_item_count(id);
+    if count == 0 {
+        return Err(Error::InvalidPacketError);
+    }
+
+    if id == items::FIST || id == items::WRENCH {
+        player.send_log("`4Oops!`` You can't trash that.");
+        return Ok(());
+    }
+
+    player.send_dialog(
+        Dialog::new()
+            .add_label_with_icon(Size::Big, &format!("`4Trash`` `w{}``", item.name), id)
+            .add_textbox(&format!("How many to destroy? You have {}.", count))
+            .add_text_input("count", "", &count.to_string(), 5)
+            .embed_data("itemID", id)
+            .end_dialog("trash_item", "Cancel", "OK"),
+    );
+
+    Ok(())
+}
+
+pub fn on_trash_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let id: u16 = dialog.parse("itemID")?;
+    let item = ITEM_MANAGER.get_item_safe(id as u32)?;
+
+    let count = match dialog.parse::<u16>("count") {
+        Ok(0) => return Ok(()),
+        Ok(count) if count <= ctx.player.get_item_count(id) => count,
+        _ => {
+            ctx.player.send_log("`4Oops!`` You don't have that many.");
+            return Ok(());
+        }
+    };
+
+    // nothing gets destroyed without seeing the count once more
+    ctx.player.send_dialog(
+        Dialog::new()
+            .add_label_with_icon(Size::Big, &format!("`4Trash`` `w{}``", item.name), id)
+            .add_textbox(&format!(
+                "Are you sure you want to destroy `w{} {}``? They are gone for good.",
+                count, item.name
+            ))
+            .add_button("confirm", &format!("`4Destroy {}``", count))
+            .add_button("cancel", "Cancel")
+            .embed_data("itemID", id)
+            .embed_data("count", count)
+            .end_dialog("trash_confirm", "", "")
+            .add_quick_exit(),
+    );
+
+    Ok(())
+}
+
+pub fn on_trash_confirm_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let id: u16 = dialog.parse("itemID")?;
+    let count: u16 = dialog.parse("count")?;
+    let item = ITEM_MANAGER.get_item_safe(id as u32)?;
+
+    match dialog.button() {
+        "confirm" => {}
+        "cancel" | "" => return Ok(()),
+        _ => return Err(Error::InvalidPacketError),
+    }
+
+    if id == items::FIST || id == items::WRENCH {
+        return Err(Error::InvalidPacketError);
+    }
+
+    // could have changed since the first dialog
+    if count == 0 || count > ctx.player.get_item_count(id) {
+        ctx.player.send_log("`4Oops!`` You don't have that many.");
+        return Ok(());
+    }
+
+    ctx.player.remove_item(id, count, true)?;
+    ctx.player
+        .send_log(&format!("`w{} {}`` trashed.", count, item.name));
+
+    Ok(())
+}
+
+// gets more expensive with every upgrade
+fn backpack_price(player: &Player) -> i32 {
+    let upgrades = player.items_slots.saturating_sub(DEFAULT_ITEMS_SLOTS) / BACKPACK_UPGRADE_SLOTS;
+    100 * (upgrades as i32 + 1)
+}
+
+pub fn send_backpack_dialog(player: &mut Player) {
+    let mut dialog = Dialog::new()
+        .add_label_with_icon(Size::Big, "`wBackpack Upgrade``", items::BACKPACK)
+        .add_textbox(&format!(
+            "Your backpack has `w{}`` slots.",
+            player.items_slots
+        ));
+
+    if player.items_slots >= MAX_ITEMS_SLOTS {
+        dialog = dialog.add_textbox("It can't get any bigger.");
+    } else {
+        let price = backpack_price(player);
+        dialog = dialog
+            .add_textbox(&format!(
+                "Get `w{}`` more for `w{}`` gems. You have `w{}``.",
+                BACKPACK_UPGRADE_SLOTS, price, player.gems
+            ))
+            .add_button("upgrade", &format!("`2Buy for {} gems``", price));
+    }
+
+    player.send_dialog(dialog.end_dialog("backpack", "", "Close"));
+}
+
+pub fn on_backpack_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    match dialog.button() {
+        "upgrade" => {}
+        "" => return Ok(()),
+        _ => return Err(Error::InvalidPacketError),
+    }
+
+    if ctx.player.items_slots >= MAX_ITEMS_SLOTS {
+        return Ok(());
+    }
+
+    let price = backpack_price(ctx.player);
+    if ctx.player.remove_gems(price).is_err() {
+        ctx.player.send_log(&format!(
+            "`4Oops!`` You need {} more gems.",
+            price - ctx.player.gems
+        ));
+        return Ok(());
+    }
+
+    ctx.player.items_slots = (ctx.player.items_slots + BACKPACK_UPGRADE_SLOTS).min(MAX_ITEMS_SLOTS);
+    ctx.player.dirty = true;
+    ctx.player.send_inventory()?;
+    ctx.player.send_log(&format!(
+        "`2Your backpack now has {} slots!``",
+        ctx.player.items_slots
+    ));
+
+    send_backpack_dialog(ctx.player);
+    Ok(())
+}
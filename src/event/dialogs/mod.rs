@@ -0,0 +1,6 @@
+pub mod account;
+pub mod friends;
+pub mod inventory;
+pub mod player;
+pub mod tile;
+pub mod trade;
@@ -0,0 +1,74 @@
+use crate::{
+    consts::items,
+    event::{dialog_return::DialogReturn, event::EventContext},
+    utils::{
+        dialog::{Dialog, Size},
+        error::{Error, Result},
+    },
+};
+
+use super::{friends, trade};
+
+/// What wrenching another player in the same world opens.
+pub fn send_wrench_dialog(ctx: &mut EventContext, net_id: i32) -> Result<()> {
+    if net_id == ctx.player.net_id {
+        return Ok(());
+    }
+
+    let world = &ctx.player.current_world;
+    let target = ctx
+        .sessions
+        .players(Some(ctx.player))
+        .find(|player| player.net_id == net_id && player.current_world == *world);
+
+    let target = match target {
+        Some(target) => target,
+        None => return Ok(()), // left already
+    };
+
+    let mut dialog = Dialog::new()
+        .add_label_with_icon(Size::Big, &target.display(), items::WRENCH)
+        .add_spacer(Size::Small);
+
+    if ctx.player.is_friend(target.user_id) {
+        dialog = dialog
+            .add_textbox(&format!("{} is your friend.", target.display()))
+            .add_button("remove_friend", "Remove friend");
+    } else {
+        dialog = dialog.add_button("add_friend", "`5Add as friend``");
+    }
+
+    let trading = ctx
+        .player
+        .trade
+        .as_ref()
+        .is_some_and(|x| x.partner == target.user_id);
+    dialog = dialog.add_button(
+        "trade",
+        if trading {
+            "`wOpen trade``"
+        } else {
+            "`wTrade``"
+        },
+    );
+
+    let dialog =
+        dialog
+            .embed_data("userID", target.user_id)
+            .end_dialog("player_wrench", "", "Close");
+
+    ctx.player.send_dialog(dialog);
+    Ok(())
+}
+
+pub fn on_wrench_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let user_id: i32 = dialog.parse("userID")?;
+
+    match dialog.button() {
+        "add_friend" => friends::send_request(ctx, user_id),
+        "remove_friend" => friends::remove(ctx, user_id),
+        "trade" => trade::request(ctx, user_id),
+        "" => Ok(()),
+        _ => Err(Error::InvalidPacketError),
+    }
+}
@@ -0,0 +1,217 @@
+use std::cell::RefMut;
+
+use crate::{
+    consts::{item_type, lock_flags},
+    database::playerdb,
+    event::{dialog_return::DialogReturn, event::EventContext},
+    utils::{
+        dialog::{Dialog, Size},
+        error::Result,
+        variant_function::VariantFunction::*,
+    },
+    world::{tileextra::TileExtra, world::World},
+};
+
+/// What wrenching a tile opens, nothing for tiles without anything to edit.
+pub fn send_wrench_dialog(
+    ctx: &mut EventContext,
+    world: &mut RefMut<World>,
+    x: u32,
+    y: u32,
+) -> Result<()> {
+    let can_edit = world.can_edit(ctx.player, x, y);
+
+    let tile = world.get_tile_safe(x, y)?;
+    let fore = tile.get_fore()?;
+    let dialog =
+        Dialog::new().add_label_with_icon(Size::Big, &format!("`wEdit {}``", fore.name), fore.id);
+
+    let dialog = match &tile.extra {
+        TileExtra::Sign { label } if can_edit => dialog
+            .add_textbox("What would you like to write on this sign?")
+            .add_text_input("label", "", label, 100)
+            .embed_data("tilex", x)
+            .embed_data("tiley", y)
+            .end_dialog("sign_edit", "Cancel", "OK"),
+
+        TileExtra::Door {
+            label,
+            destination,
+            id,
+        } if can_edit && fore.item_type != item_type::MAIN_DOOR => dialog
+            .add_text_input("label", "Label", label, 100)
+            .add_text_input("destination", "Destination", destination, 24)
+            .add_smalltext("Enter a world name, optionally followed by a colon and a door ID. Use just :ID for a door in this world.")
+            .add_text_input("door_id", "ID", id, 11)
+            .add_smalltext("Set an ID so other doors can lead here.")
+            .embed_data("tilex", x)
+            .embed_data("tiley", y)
+            .end_dialog("door_edit", "Cancel", "OK"),
+
+        TileExtra::Lock {
+            flags,
+            owner_uid,
+            owner_name,
+            access,
+        } => {
+            if *owner_uid != ctx.player.user_id {
+                ctx.player.send_varfn(OnTalkBubble(
+                    ctx.player.net_id,
+                    &format!("This lock is owned by {}.", owner_name),
+                    0,
+                    0,
+                ));
+                return Ok(());
+            }
+
+            let mut dialog = dialog.add_textbox("`wAccess list:``");
+            for (user_id, name) in access.iter() {
+                // locks from before names were kept only have the id
+                let name = if name.is_empty() {
+                    format!("#{}", user_id)
+                } else {
+                    name.to_owned()
+                };
+                dialog = dialog.add_checkbox(&format!("access_{}", user_id), &name, true);
+            }
+
+            if access.is_empty() {
+                dialog = dialog.add_textbox("Currently, you're the only one with access.");
+            }
+
+            dialog
+                .add_text_input("add_name", "Add player", "", 18)
+                .add_spacer(Size::Small)
+                .add_checkbox("public", "Allow anyone to build", flags & lock_flags::PUBLIC != 0)
+                .embed_data("tilex", x)
+                .embed_data("tiley", y)
+                .end_dialog("lock_edit", "Cancel", "OK")
+        }
+
+        _ => return Ok(()),
+    };
+
+    ctx.player.send_dialog(dialog);
+
+    Ok(())
+}
+
+pub fn on_sign_edit_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let (x, y) = dialog.tile_pos()?;
+    let world = ctx.player.get_world(ctx.world_manager)?;
+    let mut world = world.borrow_mut();
+
+    if !world.can_edit(ctx.player, x, y) {
+        return Ok(());
+    }
+
+    match &mut world.get_tile_safe(x, y)?.extra {
+        TileExtra::Sign { label } => *label = dialog.text("label", 100),
+        _ => return Ok(()), // got replaced while the dialog was open
+    }
+
+    world.dirty = true;
+    world.push_tile_update(x, y)?;
+    world.send_all();
+
+    Ok(())
+}
+
+pub fn on_door_edit_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let (x, y) = dialog.tile_pos()?;
+    let world = ctx.player.get_world(ctx.world_manager)?;
+    let mut world = world.borrow_mut();
+
+    if !world.can_edit(ctx.player, x, y) {
+        return Ok(());
+    }
+
+    // the main door never gets an edit dialog, so this was forged
+    if world.get_tile_safe(x, y)?.get_fore()?.item_type == item_type::MAIN_DOOR {
+        return Ok(());
+    }
+
+    let new_destination = dialog.text("destination", 24).to_uppercase();
+    let new_id = dialog.text("door_id", 11).to_uppercase();
+
+    let valid = |text: &str| text.chars().all(|x| x.is_ascii_alphanumeric());
+    let (world_name, door_id) = new_destination
+        .split_once(':')
+        .unwrap_or((&new_destination, ""));
+
+    if !valid(world_name) || !valid(door_id) || !valid(&new_id) {
+        ctx.player
+            .send_log("`4Oops!`` World names and door IDs can only contain letters and numbers.");
+        return Ok(());
+    }
+
+    match &mut world.get_tile_safe(x, y)?.extra {
+        TileExtra::Door {
+            label,
+            destination,
+            id,
+        } => {
+            *label = dialog.text("label", 100);
+            *destination = new_destination;
+            *id = new_id;
+        }
+        _ => return Ok(()),
+    }
+
+    world.dirty = true;
+    world.push_tile_update(x, y)?;
+    world.send_all();
+
+    Ok(())
+}
+
+pub fn on_lock_edit_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let (x, y) = dialog.tile_pos()?;
+    let world = ctx.player.get_world(ctx.world_manager)?;
+    let mut world = world.borrow_mut();
+
+    let add_name = dialog.text("add_name", 18);
+    let added = if add_name.is_empty() {
+        None
+    } else {
+        match playerdb::find_account(&add_name) {
+            Ok(account) => Some(account),
+            Err(_) => {
+                ctx.player
+                    .send_log(&format!("`4Oops!`` There is no player named {}.", add_name));
+                None
+            }
+        }
+    };
+
+    match &mut world.get_tile_safe(x, y)?.extra {
+        TileExtra::Lock {
+            flags,
+            owner_uid,
+            access,
+            ..
+        } if *owner_uid == ctx.player.user_id => {
+            // unchecked boxes remove the player
+            access.retain(|(user_id, _)| dialog.checkbox(&format!("access_{}", user_id)));
+
+            if let Some((user_id, name)) = added {
+                if user_id != *owner_uid && !access.iter().any(|(x, _)| *x == user_id) {
+                    access.push((user_id, name));
+                }
+            }
+
+            if dialog.checkbox("public") {
+                *flags |= lock_flags::PUBLIC;
+            } else {
+                *flags &= !lock_flags::PUBLIC;
+            }
+        }
+        _ => return Ok(()),
+    }
+
+    world.dirty = true;
+    world.push_tile_update(x, y)?;
+    world.send_all();
+
+    Ok(())
+}
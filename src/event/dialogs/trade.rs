@@ -0,0 +1,483 @@
+use crate::{
+    consts::items,
+    event::{dialog_return::DialogReturn, event::EventContext},
+    item::iteminfo_manager::ITEM_MANAGER,
+    player::{
+        player::Player,
+        trade::{self, Trade, MAX_TRADE_ITEMS},
+    },
+    utils::{
+        dialog::{Dialog, Size},
+        error::{Error, Result},
+    },
+};
+
+/// Asks someone in the same world to trade, or accepts if they asked first. Reopens a running trade with them.
+pub fn request(ctx: &mut EventContext, user_id: i32) -> Result<()> {
+    if let Some(partner) = trade::get_partner(ctx.player, ctx.sessions) {
+        if partner.user_id == user_id {
+            send_trade_dialog(ctx.player, partner);
+        } else {
+            ctx.player
+                .send_log("`4Oops!`` Finish your current trade first.");
+        }
+
+        return Ok(());
+    }
+
+    // whatever is left ended without us, like the partner getting kicked
+    ctx.player.trade = None;
+
+    if ctx.player.trade_requests.contains(&user_id) {
+        return accept(ctx, user_id);
+    }
+
+    let world = &ctx.player.current_world;
+    let target = match ctx.sessions.get(user_id, Some(ctx.player)) {
+        Some(target) if target.current_world == *world => target,
+        _ => {
+            ctx.player
+                .send_log("`4Oops!`` That player isn't here anymore.");
+            return Ok(());
+        }
+    };
+
+    if target.trade.is_some() {
+        ctx.player.send_log(&format!(
+            "`4Oops!`` {} is busy trading with someone else.",
+            target.display()
+        ));
+        return Ok(());
+    }
+
+    // blocked players don't find out
+    if !target.blocked.contains(&ctx.player.user_id)
+        && !target.trade_requests.contains(&ctx.player.user_id)
+    {
+        target.trade_requests.push(ctx.player.user_id);
+        target.send_dialog(
+            Dialog::new()
+                .add_label_with_icon(Size::Big, "Trade Request", items::WRENCH)
+                .add_textbox(&format!(
+                    "{} wants to trade with you.",
+                    ctx.player.display()
+                ))
+                .add_button("accept", "`2Accept``")
+                .add_button("decline", "`4Decline``")
+                .embed_data("userID", ctx.player.user_id)
+                .end_dialog("trade_request", "", "")
+                .add_quick_exit(),
+        );
+    }
+
+    ctx.player
+        .send_log(&format!("`5Asked {} to trade``.", target.display()));
+
+    Ok(())
+}
+
+pub fn on_request_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let user_id: i32 = dialog.parse("userID")?;
+
+    // can't accept requests that were never sent
+    if !ctx.player.trade_requests.contains(&user_id) {
+        return Ok(());
+    }
+
+    match dialog.button() {
+        "accept" => accept(ctx, user_id),
+        "decline" | "" => {
+            ctx.player.trade_requests.retain(|x| *x != user_id);
+            Ok(())
+        }
+        _ => Err(Error::InvalidPacketError),
+    }
+}
+
+fn accept(ctx: &mut EventContext, user_id: i32) -> Result<()> {
+    ctx.player.trade_requests.retain(|x| *x != user_id);
+
+    if trade::get_partner(ctx.player, ctx.sessions).is_some() {
+        ctx.player
+            .send_log("`4Oops!`` Finish your current trade first.");
+        return Ok(());
+    }
+
+    let world = ctx.player.current_world.to_owned();
+    let partner = match ctx.sessions.get(user_id, Some(ctx.player)) {
+        Some(partner) if partner.current_world == world && partner.trade.is_none() => partner,
+        _ => {
+            ctx.player
+                .send_log("`4Oops!`` That player can't trade right now.");
+            return Ok(());
+        }
+    };
+
+    partner.trade_requests.retain(|x| *x != ctx.player.user_id);
+    partner.trade = Some(Trade::new(ctx.player.user_id, &world));
+    ctx.player.trade = Some(Trade::new(user_id, &world));
+
+    send_trade_dialog(ctx.player, partner);
+    send_trade_dialog(partner, ctx.player);
+
+    Ok(())
+}
+
+fn add_offer(mut dialog: Dialog, trade: &Trade, removable: bool) -> Dialog {
+    if trade.items.is_empty() && trade.gems == 0 {
+        return dialog.add_smalltext("Nothing yet.");
+    }
+
+    for (id, count) in trade.items.iter() {
+        let name = &ITEM_MANAGER.get_item(*id as u32).name;
+        dialog = dialog.add_label_with_icon(Size::Small, &format!("{} x{}", name, count), *id);
+
+        if removable {
+            dialog = dialog.add_button(&format!("remove_{}", id), &format!("Remove {}", name));
+        }
+    }
+
+    // the own gems are an input instead
+    if !removable && trade.gems > 0 {
+        dialog =
+            dialog.add_label_with_icon(Size::Small, &format!("{} Gems", trade.gems), items::GEMS);
+    }
+
+    dialog
+}
+
+fn send_trade_dialog(player: &mut Player, partner: &Player) {
+    let (ours, theirs) = match (&player.trade, &partner.trade) {
+        (Some(ours), Some(theirs)) => (ours, theirs),
+        _ => return,
+    };
+
+    let mut dialog = Dialog::new()
+        .add_label_with_icon(
+            Size::Big,
+            &format!("Trading with {}", partner.display()),
+            items::WRENCH,
+        )
+        .add_spacer(Size::Small)
+        .add_label(Size::Small, "`wYour offer``");
+
+    dialog = add_offer(dialog, ours, true);
+
+    if ours.items.len() < MAX_TRADE_ITEMS {
+        dialog = dialog.add_item_picker("offer", "`wAdd item``", "Choose an item to offer");
+    }
+
+    dialog = dialog
+        .add_text_input("gems", "Gems:", &ours.gems.to_string(), 9)
+        .add_spacer(Size::Small)
+        .add_label(Size::Small, &format!("`w{}'s offer``", partner.name));
+
+    dialog = add_offer(dialog, theirs, false).add_spacer(Size::Small);
+
+    if ours.accepted {
+        dialog = dialog.add_textbox("`2You accepted.``");
+    } else {
+        dialog = dialog.add_button("accept", "`2Accept``");
+    }
+
+    if theirs.accepted {
+        dialog = dialog.add_textbox(&format!("`2{} accepted.``", partner.name));
+    }
+
+    let dialog = dialog
+        .add_smalltext("Changing either offer takes back both accepts.")
+        .add_button("cancel_trade", "`4Cancel trade``")
+        .end_dialog("trade", "", "Update");
+
+    player.send_dialog(dialog);
+}
+
+/// The second step once both accepted, showing exactly what changes hands.
+fn send_confirm_dialog(player: &mut Player, partner: &Player) {
+    let (ours, theirs) = match (&player.trade, &partner.trade) {
+        (Some(ours), Some(theirs)) => (ours, theirs),
+        _ => return,
+    };
+
+    let dialog = Dialog::new()
+        .add_label_with_icon(Size::Big, "Confirm Trade", items::WRENCH)
+        .add_spacer(Size::Small)
+        .add_label(Size::Small, "`4You give:``");
+    let dialog = add_offer(dialog, ours, false).add_label(Size::Small, "`2You get:``");
+    let dialog = add_offer(dialog, theirs, false)
+        .add_spacer(Size::Small)
+        .add_button("confirm", "`2Confirm``")
+        .add_button("back", "Back")
+        .end_dialog("trade_confirm", "", "")
+        .add_quick_exit();
+
+    player.send_dialog(dialog);
+}
+
+fn reset(player: &mut Player, partner: &mut Player) {
+    for trade in [&mut player.trade, &mut partner.trade]
+        .into_iter()
+        .flatten()
+    {
+        trade.reset();
+    }
+}
+
+/// Takes back both accepts and shows both players the new offers.
+fn update(player: &mut Player, partner: &mut Player) {
+    reset(player, partner);
+    send_trade_dialog(player, partner);
+    send_trade_dialog(partner, player);
+}
+
+fn send_offer_dialog(player: &mut Player, id: u16) -> Result<()> {
+    let item = ITEM_MANAGER.get_item_safe(id as u32)?;
+    let count = player.get_item_count(id);
+    if count == 0 {
+        return Err(Error::InvalidPacketError);
+    }
+
+    if !item.is_tradable() {
+        player.send_log("`4Oops!`` You can't trade that.");
+        return Ok(());
+    }
+
+    player.send_dialog(
+        Dialog::new()
+            .add_label_with_icon(Size::Big, &format!("`wTrade {}``", item.name), id)
+            .add_textbox("How many to offer?")
+            .add_text_input("count", "", &count.to_string(), 5)
+            .embed_data("itemID", id)
+            .end_dialog("trade_offer", "Cancel", "OK"),
+    );
+
+    Ok(())
+}
+
+pub fn on_trade_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let button = dialog.button();
+
+    if button == "cancel_trade" {
+        let reason = format!("{} cancelled the trade.", ctx.player.display());
+        trade::cancel(ctx.player, ctx.sessions, &reason);
+        ctx.player.send_log("`4Trade cancelled.``");
+        return Ok(());
+    }
+
+    let partner = match trade::get_partner(ctx.player, ctx.sessions) {
+        Some(partner) => partner,
+        None => {
+            ctx.player.trade = None;
+            return Ok(());
+        }
+    };
+
+    let gems = match dialog.parse::<i32>("gems") {
+        Ok(gems) if (0..=ctx.player.gems).contains(&gems) => Some(gems),
+        _ => {
+            ctx.player
+                .send_log("`4Oops!`` You don't have that many gems.");
+            None
+        }
+    };
+
+    let trade = ctx.player.trade.as_mut().ok_or(Error::InvalidPacketError)?;
+    let mut changed = false;
+
+    if let Some(gems) = gems.filter(|gems| *gems != trade.gems) {
+        trade.gems = gems;
+        changed = true;
+    }
+
+    match button {
+        "" | "accept" | "offer" => {}
+        _ => {
+            let id: u16 = button
+                .strip_prefix("remove_")
+                .and_then(|id| id.parse().ok())
+                .ok_or(Error::InvalidPacketError)?;
+
+            let len = trade.items.len();
+            trade.items.retain(|(x, _)| *x != id);
+            changed |= trade.items.len() != len;
+        }
+    }
+
+    if changed {
+        reset(ctx.player, partner);
+    }
+
+    if button == "accept" {
+        if let Some(trade) = ctx.player.trade.as_mut() {
+            trade.accepted = true;
+        }
+    }
+
+    let accepted = |player: &Player| player.trade.as_ref().is_some_and(|x| x.accepted);
+    if accepted(ctx.player) && accepted(partner) {
+        send_confirm_dialog(ctx.player, partner);
+        send_confirm_dialog(partner, ctx.player);
+        return Ok(());
+    }
+
+    if changed || button == "accept" {
+        send_trade_dialog(partner, ctx.player);
+    }
+
+    match button {
+        "offer" => send_offer_dialog(ctx.player, dialog.parse("offer")?)?,
+        "" if !changed => {}
+        _ => send_trade_dialog(ctx.player, partner),
+    }
+
+    Ok(())
+}
+
+pub fn on_offer_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let id: u16 = dialog.parse("itemID")?;
+    let item = ITEM_MANAGER.get_item_safe(id as u32)?;
+    if !item.is_tradable() {
+        return Err(Error::InvalidPacketError);
+    }
+
+    let partner = match trade::get_partner(ctx.player, ctx.sessions) {
+        Some(partner) => partner,
+        None => {
+            ctx.player.trade = None;
+            return Ok(());
+        }
+    };
+
+    let count = match dialog.parse::<u16>("count") {
+        Ok(count) if count > 0 && count <= ctx.player.get_item_count(id) => count,
+        _ => {
+            ctx.player.send_log("`4Oops!`` You don't have that many.");
+            return Ok(());
+        }
+    };
+
+    let trade = ctx.player.trade.as_mut().ok_or(Error::InvalidPacketError)?;
+    if let Some((_, offered)) = trade.items.iter_mut().find(|(x, _)| *x == id) {
+        *offered = count;
+    } else if trade.items.len() < MAX_TRADE_ITEMS {
+        trade.items.push((id, count));
+    } else {
+        ctx.player.send_log(&format!(
+            "`4Oops!`` You can't offer more than {} items.",
+            MAX_TRADE_ITEMS
+        ));
+        return Ok(());
+    }
+
+    update(ctx.player, partner);
+    Ok(())
+}
+
+pub fn on_confirm_return(ctx: &mut EventContext, dialog: &DialogReturn) -> Result<()> {
+    let partner = match trade::get_partner(ctx.player, ctx.sessions) {
+        Some(partner) => partner,
+        None => {
+            ctx.player.trade = None;
+            return Ok(());
+        }
+    };
+
+    let (ours, theirs) = match (ctx.player.trade.as_mut(), partner.trade.as_ref()) {
+        (Some(ours), Some(theirs)) => (ours, theirs),
+        _ => return Err(Error::InvalidPacketError),
+    };
+
+    // something changed since the confirm dialog was sent
+    if !ours.accepted || !theirs.accepted {
+        return Ok(());
+    }
+
+    match dialog.button() {
+        "confirm" => {
+            if ours.confirm(theirs) {
+                exchange(ctx.player, partner)
+            } else {
+                ctx.player.send_log(&format!(
+                    "`5Waiting for {} to confirm.``",
+                    partner.display()
+                ));
+                Ok(())
+            }
+        }
+
+        "back" | "" => {
+            update(ctx.player, partner);
+            Ok(())
+        }
+
+        _ => Err(Error::InvalidPacketError),
+    }
+}
+
+// checks everything first, so nothing can fail once items start moving
+fn exchange(player: &mut Player, partner: &mut Player) -> Result<()> {
+    let (ours, theirs) = match (player.trade.to_owned(), partner.trade.to_owned()) {
+        (Some(ours), Some(theirs)) => (ours, theirs),
+        _ => return Err(Error::InvalidPacketError),
+    };
+
+    let problem = if !trade::can_give(player, &ours) {
+        Some(format!(
+            "{} doesn't have everything they offered anymore.",
+            player.display()
+        ))
+    } else if !trade::can_give(partner, &theirs) {
+        Some(format!(
+            "{} doesn't have everything they offered anymore.",
+            partner.display()
+        ))
+    } else if !trade::can_receive(player, &ours, &theirs) {
+        Some(format!("{} doesn't have room for that.", player.display()))
+    } else if !trade::can_receive(partner, &theirs, &ours) {
+        Some(format!("{} doesn't have room for that.", partner.display()))
+    } else {
+        None
+    };
+
+    if let Some(problem) = problem {
+        player.send_log(&format!("`4Oops!`` {}", problem));
+        partner.send_log(&format!("`4Oops!`` {}", problem));
+        update(player, partner);
+        return Ok(());
+    }
+
+    player.trade = None;
+    partner.trade = None;
+
+    for (id, count) in ours.items.iter() {
+        player.remove_item(*id, *count, true)?;
+    }
+
+    for (id, count) in theirs.items.iter() {
+        partner.remove_item(*id, *count, true)?;
+    }
+
+    // can_receive made sure nothing is left over
+    for (id, count) in ours.items.iter() {
+        partner.add_item(*id, *count, true);
+    }
+
+    for (id, count) in theirs.items.iter() {
+        player.add_item(*id, *count, true);
+    }
+
+    if ours.gems > 0 {
+        player.remove_gems(ours.gems)?;
+        partner.add_gems(ours.gems);
+    }
+
+    if theirs.gems > 0 {
+        partner.remove_gems(theirs.gems)?;
+        player.add_gems(theirs.gems);
+    }
+
+    player.send_log(&format!("`2Traded with {}!``", partner.display()));
+    partner.send_log(&format!("`2Traded with {}!``", player.display()));
+
+    Ok(())
+}
@@ -1,9 +1,11 @@
 use byteorder::{ReadBytesExt, LE};
 
 use crate::{
-    consts::{self, config, message_type},
+    config::config::CONFIG,
+    consts::{self, message_type},
     enet_wrapper::host::ENetHost,
-    player::player::Player,
+    player::{player::Player, session_manager::SessionManager},
+    scheduler::scheduler::Scheduler,
     utils::error::{Error, Result},
     world::world_manager::WorldManager,
 };
@@ -13,7 +15,9 @@ use super::{tank, text};
 pub struct EventContext<'a> {
     pub host: &'a ENetHost,
     pub player: &'a mut Player,
+    pub sessions: &'a mut SessionManager,
     pub world_manager: &'a mut WorldManager,
+    pub scheduler: &'a mut Scheduler,
 
     pub text_data: &'a str,
     pub packet_data: &'a [u8],
@@ -21,9 +25,7 @@ pub struct EventContext<'a> {
 
 pub fn handle(mut ctx: EventContext) -> Result<()> {
     let mut packet_data = ctx.packet_data;
-    if packet_data.len() <= config::MINIMUM_PACKET_SIZE
-        || packet_data.len() >= config::MAXIMUM_PACKET_SIZE
-    {
+    if packet_data.len() <= CONFIG.min_packet_size || packet_data.len() >= CONFIG.max_packet_size {
         return Err(Error::InvalidPacketError);
     }
 
@@ -1,5 +1,6 @@
 pub mod commands;
 pub mod dialog_return;
+pub mod dialogs;
 pub mod event;
 pub mod tank;
 pub mod text;
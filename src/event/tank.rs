@@ -1,21 +1,26 @@
 use std::{cell::RefMut, time::Instant};
 
-use super::event::EventContext;
+use super::{dialogs::tile, event::EventContext};
 use crate::{
-    consts::{item_type, items, packet::TankUpdatePacket, packet_type},
-    item::iteminfo_manager::ITEM_MANAGER,
+    consts::{item_type, items, packet::TankUpdatePacket, packet_type, tile_flags},
+    item::{drop_table::DropTable, iteminfo::ItemInfo, iteminfo_manager::ITEM_MANAGER},
+    player::{player::Player, trade},
     utils::{
+        self,
         error::{Error, Result},
         math::Vec2f,
         variant_function::VariantFunction::*,
     },
-    world::world::World,
+    world::{tileextra::TileExtra, world::World, world_manager::WORLD_TYPE_NORMAL},
 };
 
 const RETURN_STATE_NONE: u32 = 0;
 const RETURN_STATE_REMOVE_ITEM: u32 = 1 << 0;
 const RETURN_STATE_SEND_MODIFY_ITEM_VISUAL: u32 = 1 << 1;
 
+const PICKUP_RANGE: f32 = 64.0;
+const WALK_PICKUP_RANGE: f32 = 20.0;
+
 pub fn handle(mut ctx: EventContext) -> Result<()> {
     if let Ok(world) = ctx.player.get_world(ctx.world_manager) {
         let mut world = world.borrow_mut();
@@ -25,16 +30,43 @@ pub fn handle(mut ctx: EventContext) -> Result<()> {
 
         match tankpacket.packet_type {
             packet_type::STATE => {
-                ctx.player.pos = Vec2f {
+                let pos = Vec2f {
                     x: tankpacket.pos_x,
                     y: tankpacket.pos_y,
                 };
+
+                if !can_move_to(&world, ctx.player, &pos) {
+                    let old_pos = ctx.player.pos;
+                    ctx.player
+                        .send_varfn_v(OnSetPos(old_pos), ctx.player.net_id, -1);
+                    return Ok(());
+                }
+
+                ctx.player.pos = pos;
                 ctx.player.char_flags = tankpacket.flags;
 
                 if world.peers.len() > 1 {
                     world.push_tankpacket(tankpacket);
-                    world.send_all();
                 }
+
+                // walking over objects picks them up
+                let objects: Vec<u32> = world
+                    .objects
+                    .iter()
+                    .filter(|(_, object)| object.pos.distance(&ctx.player.pos) < WALK_PICKUP_RANGE)
+                    .map(|(&id, _)| id)
+                    .collect();
+
+                for id in objects {
+                    on_object_pickup(&mut ctx, id, &mut world, false)?;
+                }
+
+                world.send_all();
+            }
+
+            packet_type::ITEM_ACTIVATE_OBJECT_REQUEST => {
+                on_object_pickup(&mut ctx, tankpacket.int_val as u32, &mut world, true)?;
+                world.send_all();
             }
 
             packet_type::TILE_CHANGE_REQUEST => {
@@ -61,6 +93,10 @@ pub fn handle(mut ctx: EventContext) -> Result<()> {
                     },
                 }
 
+                if result.is_ok() {
+                    world.dirty = true;
+                }
+
                 world.send_all();
 
                 return result;
@@ -95,10 +131,19 @@ pub fn handle(mut ctx: EventContext) -> Result<()> {
 
                 match fore.item_type {
                     item_type::MAIN_DOOR => {
+                        let reason = format!("{} left the world.", ctx.player.display());
+                        trade::cancel(ctx.player, ctx.sessions, &reason);
                         ctx.world_manager.exit_world(ctx.player, world);
                         ctx.player.send_world_menu(ctx.host);
                     }
 
+                    item_type::DOOR | item_type::PORTAL => {
+                        if let TileExtra::Door { destination, .. } = &tile.extra {
+                            let destination = destination.to_owned();
+                            return on_door_activate(&mut ctx, world, &destination);
+                        }
+                    }
+
                     _ => {}
                 }
             }
@@ -115,17 +160,187 @@ pub fn handle(mut ctx: EventContext) -> Result<()> {
     Ok(())
 }
 
+// friends entrances are the only tiles the server stops players from walking into
+fn can_move_to(world: &World, player: &Player, pos: &Vec2f) -> bool {
+    let (x, y) = ((pos.x + 10.0) / 32.0, (pos.y + 15.0) / 32.0);
+    if x < 0.0 || y < 0.0 || x as u32 >= world.width {
+        return true;
+    }
+
+    let tile = world
+        .tiles
+        .get((x as u32 + y as u32 * world.width) as usize);
+    match tile.map(|tile| tile.get_fore()) {
+        Some(Ok(fore)) if fore.item_type == item_type::FRIENDS_ENTRANCE => {
+            world.can_pass_entrance(player)
+        }
+        _ => true,
+    }
+}
+
+/// Moves as much of the object as fits into the backpack, the rest stays in the world.
+fn on_object_pickup(
+    ctx: &mut EventContext,
+    object_id: u32,
+    world: &mut RefMut<World>,
+    notify_full: bool,
+) -> Result<()> {
+    let object = match world.objects.get(&object_id) {
+        Some(object) => object.clone(),
+        None => return Ok(()), // someone else was faster
+    };
+
+    if object.pos.distance(&ctx.player.pos) > PICKUP_RANGE {
+        return Ok(());
+    }
+
+    if object.item_id == items::GEMS {
+        world.take_object(object_id, object.count, ctx.player.net_id)?;
+        ctx.player.add_gems(object.count as i32);
+        return Ok(());
+    }
+
+    let count = ctx.player.item_room(object.item_id).min(object.count);
+    if count == 0 {
+        if notify_full {
+            ctx.player.send_log("`4Oops!`` Your backpack is full.");
+        }
+
+        return Ok(());
+    }
+
+    let item = ITEM_MANAGER.get_item_safe(object.item_id as u32)?;
+    world.take_object(object_id, count, ctx.player.net_id)?;
+    // the client adds fully collected objects to the inventory by itself
+    ctx.player
+        .add_item(object.item_id, count, count < object.count);
+    ctx.player.send_varfn(OnConsoleMessage(&format!(
+        "Collected `w{} {}``.",
+        count, item.name
+    )));
+
+    Ok(())
+}
+
+/// Warps the player to `WORLD:DOORID`, `WORLD` or `:DOORID` for a door in the same world.
+fn on_door_activate(
+    ctx: &mut EventContext,
+    mut world: RefMut<World>,
+    destination: &str,
+) -> Result<()> {
+    if destination.is_empty() {
+        return Ok(());
+    }
+
+    let (world_name, door_id) = destination.split_once(':').unwrap_or((destination, ""));
+
+    if world_name.is_empty() || world_name.eq_ignore_ascii_case(&world.name) {
+        let pos = if door_id.is_empty() {
+            world.door_pos
+        } else {
+            match world.find_door(door_id) {
+                Some(pos) => pos,
+                None => {
+                    ctx.player.send_varfn(OnTalkBubble(
+                        ctx.player.net_id,
+                        &format!("`4Oops!`` There is no door with the ID {} here.", door_id),
+                        0,
+                        0,
+                    ));
+                    return Ok(());
+                }
+            }
+        };
+
+        ctx.player.pos = pos;
+        ctx.player
+            .send_varfn_v(OnSetPos(pos), ctx.player.net_id, -1);
+        world.push_varfn_v(
+            OnPlayPositioned("audio/door_open.wav"),
+            ctx.player.net_id,
+            -1,
+        );
+        world.send_all();
+
+        return Ok(());
+    }
+
+    // join_world takes the player out of this one
+    drop(world);
+
+    if world_name.len() > 15 {
+        ctx.player.send_varfn(OnTalkBubble(
+            ctx.player.net_id,
+            "`4Oops!`` This door leads to a world with a name that is too long.",
+            0,
+            0,
+        ));
+        return Ok(());
+    }
+
+    let target = ctx
+        .world_manager
+        .get_or_create(world_name, WORLD_TYPE_NORMAL);
+    let pos = if door_id.is_empty() {
+        None
+    } else {
+        let pos = target.borrow().find_door(door_id);
+        if pos.is_none() {
+            ctx.player.send_log(&format!(
+                "`4Oops!`` {} has no door with the ID {}, so you arrived at the main door.",
+                world_name, door_id
+            ));
+        }
+
+        pos
+    };
+
+    let reason = format!("{} left the world.", ctx.player.display());
+    trade::cancel(ctx.player, ctx.sessions, &reason);
+
+    ctx.world_manager
+        .join_world(ctx.player, world_name, pos, true)
+}
+
 fn on_tile_punch_request(
-    _: &mut EventContext,
+    ctx: &mut EventContext,
     mut tankpacket: TankUpdatePacket,
     world: &mut RefMut<World>,
 ) -> Result<()> {
-    let tile = world.get_tile_safe(tankpacket.tile_x as u32, tankpacket.tile_y as u32)?;
+    let (x, y) = (tankpacket.tile_x as u32, tankpacket.tile_y as u32);
+    if !check_access(ctx, world, x, y) {
+        return Ok(());
+    }
+
+    let tile = world.get_tile_safe(x, y)?;
     let base = tile.get_base()?;
     if base.id == items::BLANK {
         return Ok(());
     }
 
+    // access is enough to build, but only the owner can take the lock away
+    if tile.extra.is_lock() && tile.fore == base.id {
+        if !tile.extra.is_owner(ctx.player.user_id) {
+            ctx.player.send_varfn(OnTalkBubble(
+                ctx.player.net_id,
+                "Only the owner can break this lock.",
+                0,
+                0,
+            ));
+            return Ok(());
+        }
+
+        if ctx.player.item_room(base.id) == 0 {
+            ctx.player
+                .send_log("`4Oops!`` You don't have enough room in your backpack for the lock.");
+            return Ok(());
+        }
+    }
+
+    if tile.is_ripe()? {
+        return on_tree_harvest(ctx, tankpacket, world);
+    }
+
     if (Instant::now() - tile.last_punch).as_secs() > base.heal_time as u64 {
         tile.hit_count = 0;
         tile.last_punch = Instant::now();
@@ -136,39 +351,201 @@ fn on_tile_punch_request(
     tankpacket.packet_type = packet_type::TILE_APPLY_DAMAGE;
     tankpacket.int_val = 6; // tile damage
 
+    let mut drop = None;
+    let mut lock_broken = false;
     if tile.hit_count >= base.hits_to_destroy {
         tankpacket.packet_type = packet_type::TILE_CHANGE_REQUEST;
         tankpacket.int_val = items::FIST as i32;
+
+        // locks go back to the owner instead of dropping anything
+        lock_broken = tile.extra.is_lock() && tile.fore == base.id;
+        if !lock_broken {
+            drop = DropTable::for_item(base).roll(base);
+        }
+
         tile.remove_base();
     }
 
     world.push_tankpacket(tankpacket);
 
+    if lock_broken {
+        on_lock_broken(ctx, world, x, y, base)?;
+    }
+
+    if let Some((item_id, count)) = drop {
+        let pos = Vec2f::new(
+            (x * 32) as f32 + utils::random(0.0..16.0),
+            (y * 32) as f32 + utils::random(0.0..16.0),
+        );
+
+        world.drop_object(item_id, count, pos);
+    }
+
     Ok(())
 }
 
-fn on_tile_wrench_request(
-    _: &mut EventContext,
-    _: TankUpdatePacket,
-    _: &mut RefMut<World>,
+/// Tells the player who owns the tile and returns false if they can't edit it.
+fn check_access(ctx: &mut EventContext, world: &World, x: u32, y: u32) -> bool {
+    if world.can_edit(ctx.player, x, y) {
+        return true;
+    }
+
+    if let Some(TileExtra::Lock { owner_name, .. }) = world.get_lock(x, y) {
+        ctx.player.send_varfn(OnTalkBubble(
+            ctx.player.net_id,
+            &format!("That area is owned by {}.", owner_name),
+            0,
+            0,
+        ));
+    }
+
+    false
+}
+
+fn on_lock_placed(
+    ctx: &mut EventContext,
+    world: &mut RefMut<World>,
+    x: u32,
+    y: u32,
+    lock: &ItemInfo,
+) -> Result<()> {
+    let index = x + y * world.width;
+
+    if lock.is_world_lock() {
+        world.world_lock = Some(index);
+
+        let message = format!(
+            "`5[```w{}`` has been `$World Locked`` by {}`5]``",
+            world.name,
+            ctx.player.display()
+        );
+        world.push_varfn(OnTalkBubble(ctx.player.net_id, &message, 0, 0));
+        world.push_varfn(OnConsoleMessage(&message));
+    } else {
+        let claimed = world.claim_area(index, lock.lock_area_size());
+        world.push_lock_update(x, y, lock.id, ctx.player.net_id, &claimed);
+    }
+
+    Ok(())
+}
+
+fn on_lock_broken(
+    ctx: &mut EventContext,
+    world: &mut RefMut<World>,
+    x: u32,
+    y: u32,
+    lock: &ItemInfo,
+) -> Result<()> {
+    let index = x + y * world.width;
+
+    if world.world_lock == Some(index) {
+        world.world_lock = None;
+
+        let message = format!(
+            "`5[```w{}`` has had its `$World Lock`` removed!`5]``",
+            world.name
+        );
+        world.push_varfn(OnConsoleMessage(&message));
+    } else {
+        let width = world.width;
+        for released in world.release_area(index) {
+            let released = released as u32;
+            world.push_tile_update(released % width, released / width)?;
+        }
+    }
+
+    // room for it was checked before breaking it
+    ctx.player.add_item(lock.id, 1, true);
+    Ok(())
+}
+
+fn on_tree_harvest(
+    ctx: &mut EventContext,
+    tankpacket: TankUpdatePacket,
+    world: &mut RefMut<World>,
 ) -> Result<()> {
+    let tile = world.get_tile_safe(tankpacket.tile_x as u32, tankpacket.tile_y as u32)?;
+    let seed = tile.get_fore()?;
+    let fruit_count = match tile.extra {
+        TileExtra::Seed { fruit_count, .. } => fruit_count,
+        _ => return Ok(()),
+    };
+
+    // seeds always come right after their block
+    let mut harvest = vec![(seed.id - 1, fruit_count as u16)];
+    if utils::random(0..4) == 0 {
+        harvest.push((seed.id, 1));
+    }
+
+    tile.remove_base();
+
+    // whatever doesn't fit falls on the ground
+    for (id, count) in harvest {
+        let leftover = ctx.player.add_item(id, count, true);
+        if leftover > 0 {
+            let pos = Vec2f::new(
+                (tankpacket.tile_x * 32) as f32 + utils::random(0.0..16.0),
+                (tankpacket.tile_y * 32) as f32 + utils::random(0.0..16.0),
+            );
+
+            world.drop_object(id, leftover, pos);
+        }
+    }
+
+    let mut tree_state = TankUpdatePacket::default();
+    tree_state.packet_type = packet_type::SEND_TILE_TREE_STATE;
+    tree_state.net_id = ctx.player.net_id;
+    tree_state.target_id = -1;
+    tree_state.tile_x = tankpacket.tile_x;
+    tree_state.tile_y = tankpacket.tile_y;
+    world.push_tankpacket(tree_state);
+
     Ok(())
 }
 
+fn on_tile_wrench_request(
+    ctx: &mut EventContext,
+    tankpacket: TankUpdatePacket,
+    world: &mut RefMut<World>,
+) -> Result<()> {
+    let (x, y) = (tankpacket.tile_x as u32, tankpacket.tile_y as u32);
+    tile::send_wrench_dialog(ctx, world, x, y)
+}
+
 fn on_tile_build_request(
     ctx: &mut EventContext,
     tankpacket: TankUpdatePacket,
     world: &mut RefMut<World>,
 ) -> Result<u32> {
-    let tile = world.get_tile_safe(tankpacket.tile_x as u32, tankpacket.tile_y as u32)?;
-    let fore = tile.get_fore()?;
-    let base = tile.get_base()?;
+    let (x, y) = (tankpacket.tile_x as u32, tankpacket.tile_y as u32);
     let item = ITEM_MANAGER.get_item_safe(tankpacket.int_val as u32)?;
 
     if !ctx.player.has_item(item.id) {
         return Err(Error::InvalidPacketError);
     }
 
+    if !check_access(ctx, world, x, y) {
+        return Ok(RETURN_STATE_NONE);
+    }
+
+    if item.item_type == item_type::LOCK {
+        if item.is_world_lock() && world.world_lock.is_some() {
+            ctx.player
+                .send_log("`4Oops!`` This world is already locked.");
+            return Ok(RETURN_STATE_NONE);
+        }
+
+        if !item.is_world_lock() && world.get_tile_safe(x, y)?.has_flag(tile_flags::LOCKED) {
+            ctx.player
+                .send_log("`4Oops!`` You can't place a lock inside the area of another lock.");
+            return Ok(RETURN_STATE_NONE);
+        }
+    }
+
+    let tile = world.get_tile_safe(x, y)?;
+    let fore = tile.get_fore()?;
+    let base = tile.get_base()?;
+
     if base.id != items::BLANK {}
 
     if item.item_type == item_type::BACKGROUND
@@ -181,10 +558,11 @@ fn on_tile_build_request(
             return Ok(RETURN_STATE_NONE);
         }
 
-        match item.item_type {
-            _ => {
-                // there is guild item and flag in flags2 but i dont wanna share it :PP
-                if item.extra {
+        // there is guild item and flag in flags2 but i dont wanna share it :PP
+        if item.extra {
+            match TileExtra::for_item(item, ctx.player) {
+                Some(extra) => tile.extra = extra,
+                None => {
                     ctx.player.send_log("`4Oops!`` This item is not handled yet. Please report to one of the developers.");
                     return Ok(RETURN_STATE_NONE);
                 }
@@ -192,9 +570,22 @@ fn on_tile_build_request(
         }
 
         tile.set_fore(item.id);
+
+        if item.item_type == item_type::SEED {
+            tile.add_flag(tile_flags::SEED);
+        }
     }
 
+    let has_extra = tile.extra != TileExtra::None;
     world.push_tankpacket(tankpacket);
 
+    if has_extra {
+        world.push_tile_update(x, y)?;
+    }
+
+    if item.item_type == item_type::LOCK {
+        on_lock_placed(ctx, world, x, y, item)?;
+    }
+
     Ok(RETURN_STATE_REMOVE_ITEM)
 }
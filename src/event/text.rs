@@ -1,15 +1,24 @@
 use std::collections::HashMap;
 
 use crate::{
-    database::playerdb,
+    database::{
+        bandb::{self, PunishmentKind},
+        playerdb,
+    },
     item::iteminfo_manager::ITEM_MANAGER,
+    player::{player::Player, session_manager::SessionManager, trade},
     utils::{
         error::{Error, Result},
         variant_function::VariantFunction::*,
     },
+    world::world_manager::WorldManager,
 };
 
-use super::{commands, dialog_return, event::EventContext};
+use super::{
+    commands, dialog_return,
+    dialogs::{self, account, friends, inventory},
+    event::EventContext,
+};
 
 fn get_map_from_str(text: &str) -> Result<(&str, HashMap<&str, &str>)> {
     let mut map = HashMap::new();
@@ -46,10 +55,41 @@ fn get_map_from_str(text: &str) -> Result<(&str, HashMap<&str, &str>)> {
     Ok((key, map))
 }
 
+// a second login kicks the older session, which is saved first so nothing it did gets lost
+fn start_session(
+    player: &mut Player,
+    sessions: &mut SessionManager,
+    world_manager: &mut WorldManager,
+) -> Result<()> {
+    if let Some(old) = sessions.get(player.user_id, Some(player)) {
+        old.send_log("`4Someone logged into your account from somewhere else.``");
+
+        if let Ok(world) = old.get_world(world_manager) {
+            world_manager.exit_world(old, world.borrow_mut());
+        }
+
+        playerdb::save_player(old)?;
+        old.authenticated = false; // so it isn't saved again once it disconnects
+        old.disconnect();
+
+        playerdb::load_player(player)?;
+    }
+
+    sessions.register(player);
+    sessions.notify_friends(
+        player,
+        &format!("`3FRIEND ALERT:`` {} has `2logged on``.", player.display()),
+    );
+
+    Ok(())
+}
+
 pub fn handle(ctx: EventContext) -> Result<()> {
     let text = ctx.text_data;
     let player = ctx.player;
+    let sessions = ctx.sessions;
     let world_manager = ctx.world_manager;
+    let scheduler = ctx.scheduler;
 
     if text.chars().any(|x| !x.is_ascii()) {
         return Err(Error::InvalidPacketError);
@@ -65,7 +105,9 @@ pub fn handle(ctx: EventContext) -> Result<()> {
                 let ctx = EventContext {
                     host: ctx.host,
                     player,
+                    sessions,
                     world_manager,
+                    scheduler,
                     text_data: text,
                     packet_data: ctx.packet_data,
                 };
@@ -82,27 +124,12 @@ pub fn handle(ctx: EventContext) -> Result<()> {
             "action" => match map.get("action") {
                 Some(&action) => match action {
                     "enter_game" => {
-                        const DIALOG: &str = concat!(
-                            "set_default_color|`o|\n",
-                            "add_label_with_icon|big|Grow Rust|left|18|\n",
-                            "add_spacer|small|\n",
-                            "add_textbox|Hello player! You are in `6Grow Rust`` server!|left|\n",
-                            "add_textbox|This server was made by `6kevz#2211``.|left|\n",
-                            "add_textbox|What makes this server unique is that it was built with the `6Rust`` programming language.|left|\n",
-                            "add_textbox|This project is opensourced at my (`6kevz#2211``) github.|left|\n",
-                            "add_textbox|Anyways, if you are going to use this server as base server, don't forget to credit me! :D|left|\n",
-                            "add_spacer|small|\n",
-                            "add_url_button||`1Kevz's Github``|NOFLAGS|https://github.com/zKevz|Visit `1Kevz's Github``?|0|0|\n",
-                            "add_url_button||`1Project's Repository``|NOFLAGS|https://github.com/zKevz/GrowRust|Visit this project repository in github?|0|0|\n",
-                            "add_spacer|small|\n",
-                            "end_dialog|lol||Close|\n",
-                            "add_quick_exit\n"
-                        );
-
                         player.send_log("`9Welcome to `6Grow Rust``!``");
                         player.send_inventory()?;
+                        player.send_varfn(OnSetBux(player.gems));
                         player.send_world_menu(ctx.host);
-                        player.send_varfn(OnDialogRequest(DIALOG));
+                        player.send_mail();
+                        account::send_welcome_dialog(player);
                     }
 
                     "refresh_item_data" => {
@@ -111,11 +138,66 @@ pub fn handle(ctx: EventContext) -> Result<()> {
 
                     "join_request" => match map.get("name") {
                         Some(name) => {
+                            let reason = format!("{} left the world.", player.display());
+                            trade::cancel(player, sessions, &reason);
                             world_manager.join_world(player, name, None, true)?;
                         }
                         _ => {}
                     },
 
+                    "drop" => {
+                        let id: u16 = match map.get("itemID") {
+                            Some(id) => id.parse()?,
+                            None => return Err(Error::InvalidPacketError),
+                        };
+
+                        inventory::send_drop_dialog(player, id)?;
+                    }
+
+                    "trash" => {
+                        let id: u16 = match map.get("itemID") {
+                            Some(id) => id.parse()?,
+                            None => return Err(Error::InvalidPacketError),
+                        };
+
+                        inventory::send_trash_dialog(player, id)?;
+                    }
+
+                    "store" => inventory::send_backpack_dialog(player),
+
+                    "wrench" => {
+                        let net_id: i32 = match map.get("netid") {
+                            Some(net_id) => net_id.parse()?,
+                            None => return Err(Error::InvalidPacketError),
+                        };
+
+                        let mut ctx = EventContext {
+                            host: ctx.host,
+                            player,
+                            sessions,
+                            world_manager,
+                            scheduler,
+                            text_data: text,
+                            packet_data: ctx.packet_data,
+                        };
+
+                        dialogs::player::send_wrench_dialog(&mut ctx, net_id)?;
+                    }
+
+                    "friends" => {
+                        let mut ctx = EventContext {
+                            host: ctx.host,
+                            player,
+                            sessions,
+                            world_manager,
+                            scheduler,
+                            text_data: text,
+                            packet_data: ctx.packet_data,
+                        };
+
+                        friends::send_friends_dialog(&mut ctx);
+                    }
+
                     "getDRAnimations" => {}
 
                     "respawn" => {
@@ -142,12 +224,19 @@ pub fn handle(ctx: EventContext) -> Result<()> {
                                 let ctx = EventContext {
                                     host: ctx.host,
                                     player,
+                                    sessions,
                                     world_manager,
+                                    scheduler,
                                     text_data: text,
                                     packet_data: ctx.packet_data,
                                 };
 
                                 commands::handle(ctx)?;
+                            } else if let Some(mute) = bandb::find(PunishmentKind::Mute, player) {
+                                player.send_log(&format!(
+                                    "`4You are muted`` for {}.",
+                                    mute.remaining()
+                                ));
                             } else {
                                 if let Ok(world) = player.get_world(world_manager) {
                                     let chat_color = player.get_chat_color();
@@ -175,6 +264,9 @@ pub fn handle(ctx: EventContext) -> Result<()> {
                     },
 
                     "quit_to_exit" => {
+                        let reason = format!("{} left the world.", player.display());
+                        trade::cancel(player, sessions, &reason);
+
                         if let Ok(world) = player.get_world(world_manager) {
                             world_manager.exit_world(player, world.borrow_mut());
                             player.send_world_menu(ctx.host);
@@ -223,22 +315,17 @@ pub fn handle(ctx: EventContext) -> Result<()> {
                 player.platform_id = get_value("platformID")?.parse()?;
                 player.f = get_value("f")? == "1";
 
+                if let Some(ban) = bandb::find(PunishmentKind::Ban, player) {
+                    player.send_log(&format!(
+                        "`4This account or device is banned`` for {}. Reason: {}",
+                        ban.remaining(),
+                        ban.reason
+                    ));
+                    return Err(Error::Disconnected);
+                }
+
                 if player.is_guest() {
-                    const DIALOG: &str = concat!(
-                        "set_default_color|`o|\n",
-                        "add_label_with_icon|big|GrowID Creation|left|204|\n",
-                        "add_spacer|small|\n",
-                        "add_button|create|`6Create!``|noflags|0|0|\n",
-                        "add_text_input|name|GrowID:||12|\n",
-                        "add_smalltext|GrowID should be between 3 and 12 characters long.|\n",
-                        "add_text_input_password|pass|Password:||18|\n",
-                        "add_text_input_password|verify_pass|Verify Password:||18|\n",
-                        "add_smalltext|Password should be between 8 and 18 characters long.|\n",
-                        "add_spacer|small|\n",
-                        "end_dialog|growid|||\n"
-                    );
-
-                    player.send_varfn(OnDialogRequest(DIALOG));
+                    account::send_growid_dialog(player);
                     return Ok(());
                 } else {
                     match playerdb::authenticate_player(player) {
@@ -250,6 +337,7 @@ pub fn handle(ctx: EventContext) -> Result<()> {
                     }
                 }
 
+                start_session(player, sessions, world_manager)?;
                 player.authenticated = true;
                 player.send_varfn(OnSuperMain);
             }
@@ -0,0 +1,148 @@
+use std::{collections::HashMap, fs, path::Path, process};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use super::{iteminfo::ItemInfo, iteminfo_manager::ITEM_MANAGER};
+use crate::{
+    config::config::CONFIG,
+    consts::{item_flags, item_type, items},
+    utils::{
+        self,
+        error::{Error, Result},
+    },
+};
+
+// items with this rarity (or above) can't be obtained by breaking blocks
+const NO_RARITY: u16 = 999;
+
+lazy_static! {
+    /// Per item overrides loaded from `drops_path`, every other item uses `DropTable::from_rarity`.
+    pub static ref DROP_TABLES: HashMap<u16, DropTable> = match load_from_file(&CONFIG.drops_path) {
+        Ok(tables) => tables,
+        Err(e) => {
+            eprintln!("Error: Could not load drop tables '{}': {}", CONFIG.drops_path, e);
+            process::exit(1);
+        }
+    };
+}
+
+/// What breaking a block can drop, only one of them is rolled per break.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DropTable {
+    pub block_chance: f32,
+    pub seed_chance: f32,
+    pub gem_chance: f32,
+    pub min_gems: u8,
+    pub max_gems: u8,
+}
+
+impl DropTable {
+    pub fn for_item(item: &ItemInfo) -> DropTable {
+        match DROP_TABLES.get(&item.id) {
+            Some(table) => table.clone(),
+            None => DropTable::from_rarity(item),
+        }
+    }
+
+    /// The rarer the item, the less likely its seed drops and the more gems it gives.
+    pub fn from_rarity(item: &ItemInfo) -> DropTable {
+        let mut table = DropTable {
+            block_chance: 0.0,
+            seed_chance: 0.0,
+            gem_chance: 0.0,
+            min_gems: 0,
+            max_gems: 0,
+        };
+
+        if item.rarity >= NO_RARITY
+            || item.item_type == item_type::SEED
+            || item.has_flag(item_flags::DROPLESS)
+        {
+            return table;
+        }
+
+        let rarity = item.rarity as f32;
+
+        table.block_chance = 0.1;
+        table.gem_chance = 0.4;
+        table.min_gems = 1;
+        table.max_gems = (item.rarity / 10).clamp(1, 20) as u8;
+
+        if has_seed(item) {
+            table.seed_chance = 0.25 / (1.0 + rarity / 50.0);
+        }
+
+        table
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let chances = [self.block_chance, self.seed_chance, self.gem_chance];
+        if chances.iter().any(|x| *x < 0.0) || chances.iter().sum::<f32>() > 1.0 {
+            return Err(Error::ConfigError(
+                "chances must be positive and add up to 1 at most".to_string(),
+            ));
+        }
+
+        if self.min_gems > self.max_gems {
+            return Err(Error::ConfigError(
+                "min_gems cannot be bigger than max_gems".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The item id and count that dropped, if anything did.
+    pub fn roll(&self, item: &ItemInfo) -> Option<(u16, u16)> {
+        let mut roll = utils::random(0.0..1.0);
+
+        if roll < self.block_chance {
+            return Some((item.id, 1));
+        }
+        roll -= self.block_chance;
+
+        if roll < self.seed_chance && has_seed(item) {
+            return Some((item.id + 1, 1));
+        }
+        roll -= self.seed_chance;
+
+        if roll < self.gem_chance && self.max_gems > 0 {
+            return Some((
+                items::GEMS,
+                utils::random(self.min_gems.max(1)..=self.max_gems) as u16,
+            ));
+        }
+
+        None
+    }
+}
+
+// seeds always come right after their block
+fn has_seed(item: &ItemInfo) -> bool {
+    !item.has_flag(item_flags::SEEDLESS)
+        && ITEM_MANAGER
+            .get_item_safe(item.id as u32 + 1)
+            .map_or(false, |seed| seed.item_type == item_type::SEED)
+}
+
+/// No file means no overrides.
+pub fn load_from_file(path: &str) -> Result<HashMap<u16, DropTable>> {
+    if path.is_empty() || !Path::new(path).exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = fs::read_to_string(path)?;
+    let tables = serde_json::from_str::<HashMap<u16, DropTable>>(&content)?;
+
+    for (id, table) in tables.iter() {
+        if ITEM_MANAGER.get_item_safe(*id as u32).is_err() {
+            return Err(Error::ConfigError(format!("item {} doesn't exist", id)));
+        }
+
+        table.validate()?;
+    }
+
+    Ok(tables)
+}
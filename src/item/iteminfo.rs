@@ -1,5 +1,10 @@
 #![allow(dead_code)]
 
+use crate::{
+    config::config::CONFIG,
+    consts::{item_flags, item_type, items},
+};
+
 #[derive(Debug)]
 // renamed version of ItemInfo cuz i dont want to share it :P
 pub struct ItemInfo {
@@ -63,6 +68,41 @@ pub struct ItemInfo {
     pub extra: bool,
 }
 
+impl ItemInfo {
+    pub fn has_flag(&self, flag: u32) -> bool {
+        (self.flags as u32) & flag != 0
+    }
+
+    pub fn is_world_lock(&self) -> bool {
+        self.item_type == item_type::LOCK && self.has_flag(item_flags::WORLD_LOCK)
+    }
+
+    /// How many tiles an area lock claims around itself.
+    pub fn lock_area_size(&self) -> usize {
+        match self.id {
+            items::BIG_LOCK => 48,
+            items::HUGE_LOCK | items::BUILDER_S_LOCK => 200,
+            _ => 10,
+        }
+    }
+
+    /// How many fit in one backpack slot or world object.
+    pub fn max_count(&self) -> u16 {
+        match self.maxcount {
+            0 => CONFIG.max_items_count,
+            maxcount => maxcount as u16,
+        }
+    }
+
+    pub fn is_droppable(&self) -> bool {
+        self.is_tradable()
+    }
+
+    pub fn is_tradable(&self) -> bool {
+        self.id != items::FIST && self.id != items::WRENCH && !self.has_flag(item_flags::UNTRADABLE)
+    }
+}
+
 impl Default for ItemInfo {
     fn default() -> Self {
         Self {
@@ -5,6 +5,7 @@ use lazy_static::lazy_static;
 
 use super::iteminfo::ItemInfo;
 use crate::{
+    config::config::CONFIG,
     consts::{item_type, packet::TankUpdatePacket},
     utils::{
         self,
@@ -15,7 +16,7 @@ use crate::{
 
 lazy_static! {
     pub static ref ITEM_MANAGER: ItemInfoManager =
-        ItemInfoManager::load_from_file("data/items.dat").unwrap();
+        ItemInfoManager::load_from_file(&CONFIG.items_path).unwrap();
 }
 pub struct ItemInfoManager {
     pub hash: u32,
@@ -197,11 +198,13 @@ impl ItemInfoManager {
                 | item_type::WEATHER_INFINITY
                 | item_type::COMPLETIONIST
                 | item_type::FEEDING_BLOCK
-                | item_type::KRANKENS_BLOCK
-                | item_type::FRIENDS_ENTRANCE => {
+                | item_type::KRANKENS_BLOCK => {
                     item.extra = true;
                 }
 
+                // no extra data, who gets through is decided per player, see `World::can_pass_entrance`
+                item_type::FRIENDS_ENTRANCE => {}
+
                 _ => {}
             }
 
@@ -1,2 +1,3 @@
+pub mod drop_table;
 pub mod iteminfo;
 pub mod iteminfo_manager;
@@ -1,8 +1,11 @@
+pub mod config;
+pub mod console;
 pub mod consts;
 pub mod database;
 pub mod enet_wrapper;
 pub mod event;
 pub mod item;
 pub mod player;
+pub mod scheduler;
 pub mod utils;
 pub mod world;
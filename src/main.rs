@@ -1,40 +1,85 @@
-use std::{fs, net::Ipv4Addr, path::Path, thread, time::Duration};
+use std::{
+    fs,
+    net::Ipv4Addr,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
 
 use server::{
+    config::config::CONFIG,
+    console::console::{self, Console},
     consts,
-    database::playerdb,
+    database::{bandb::BAN_LIST, playerdb, storage::STORAGE},
     enet_wrapper::{self, event::ENetEventType, host::ENetHost},
     event::event::{self, EventContext},
-    item::iteminfo_manager::ITEM_MANAGER,
-    player::player::Player,
+    item::{drop_table::DROP_TABLES, iteminfo_manager::ITEM_MANAGER},
+    player::{player::Player, session_manager::SessionManager, trade},
+    scheduler::scheduler::Scheduler,
     world::world_manager::WorldManager,
 };
 
-const PORT: u16 = 10000;
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+static RUNNING: AtomicBool = AtomicBool::new(true);
 
 fn main() {
     println!("GrowRust made by kevz#2211!");
 
+    CONFIG.touch();
     enet_wrapper::initialize();
 
-    if !Path::new("data").exists() {
-        fs::create_dir("data").unwrap();
+    if let Some(parent) = Path::new(&CONFIG.items_path).parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent).unwrap();
+        }
     }
 
-    if !Path::new("data/items.dat").exists() {
-        eprintln!("Error: Could not find items.dat in 'data/items.dat'!");
+    if !Path::new(&CONFIG.items_path).exists() {
+        eprintln!(
+            "Error: Could not find items.dat in '{}'!",
+            CONFIG.items_path
+        );
         thread::sleep(Duration::from_secs(5));
         return;
     }
 
     ITEM_MANAGER.touch();
+    lazy_static::initialize(&DROP_TABLES);
+    lazy_static::initialize(&STORAGE);
+    lazy_static::initialize(&BAN_LIST);
 
-    let mut host = ENetHost::new(Ipv4Addr::UNSPECIFIED, PORT, 1024);
+    // handles both SIGINT and SIGTERM
+    ctrlc::set_handler(|| RUNNING.store(false, Ordering::SeqCst))
+        .expect("Failed to set the shutdown handler!");
+
+    let mut host = ENetHost::new(Ipv4Addr::UNSPECIFIED, CONFIG.port, CONFIG.max_peers);
     let mut world_manager = WorldManager::new();
+    let mut sessions = SessionManager::new();
+    let mut scheduler = Scheduler::new();
+    let console = Console::spawn();
+
+    scheduler.schedule_repeating(CONFIG.autosave_interval(), |ctx| {
+        let worlds = ctx.world_manager.save_worlds(false);
+        let players = save_players(ctx.host, false);
 
-    println!("Server is running..");
+        if worlds > 0 || players > 0 {
+            println!("Autosaved {} worlds and {} players.", worlds, players);
+        }
+
+        Ok(())
+    });
+
+    println!("Server is running.. Type help to see the console commands.");
+
+    while RUNNING.load(Ordering::SeqCst) {
+        scheduler.tick(&host, &mut world_manager);
+
+        while let Some(line) = console.poll() {
+            console::handle(&line, &mut sessions, &mut world_manager);
+        }
 
-    loop {
         if let Some(event) = host.service(5) {
             //let time_start = Instant::now();
 
@@ -54,13 +99,28 @@ fn main() {
                             world_manager.exit_world(player, world.borrow_mut());
                         }
 
+                        let reason = format!("{} left.", player.display());
+                        trade::cancel(player, &mut sessions, &reason);
+                        sessions.unregister(player);
+
+                        if player.authenticated {
+                            sessions.notify_friends(
+                                player,
+                                &format!(
+                                    "`3FRIEND ALERT:`` {} has `4logged off``.",
+                                    player.display()
+                                ),
+                            );
+                        }
+
                         if player.authenticated && !player.is_guest() {
                             match playerdb::save_player(player) {
                                 Ok(_) => {}
-                                Err(e) => println!("Failed to save player! Error: {}", e),
+                                Err(e) => println!("Failed to save player! Error: {:?}", e),
                             }
                         }
 
+                        scheduler.cancel_for_peer(peer.get_inner());
                         peer.set_data::<Player>(None);
                         println!("Player disconnected!");
                     }
@@ -73,7 +133,9 @@ fn main() {
                         let ctx = EventContext {
                             host: &host,
                             player: player,
+                            sessions: &mut sessions,
                             world_manager: &mut world_manager,
+                            scheduler: &mut scheduler,
 
                             text_data: "",
                             packet_data: enet_packet.get_data(),
@@ -94,4 +156,63 @@ fn main() {
             // println!("Event took {} microseconds!", micros);
         }
     }
+
+    shutdown(&mut host, &mut world_manager);
+}
+
+fn save_players(host: &ENetHost, force: bool) -> usize {
+    let mut count = 0;
+
+    for player in host.get_peers_data::<Player>() {
+        if !player.authenticated || player.is_guest() || (!force && !player.dirty) {
+            continue;
+        }
+
+        match playerdb::save_player(player) {
+            Ok(_) => count += 1,
+            Err(e) => println!("Failed to save player {}! Error: {:?}", player.name, e),
+        }
+    }
+
+    count
+}
+
+fn shutdown(host: &mut ENetHost, world_manager: &mut WorldManager) {
+    println!("Shutting down..");
+
+    // save everything first, so nothing is lost even if the peers dont disconnect in time
+    let players = save_players(host, true);
+    let worlds = world_manager.save_worlds(true);
+    println!("Saved {} worlds and {} players.", worlds, players);
+
+    for player in host.get_peers_data::<Player>() {
+        player.send_log("`4The server is shutting down. See you soon!``");
+        player.disconnect();
+    }
+
+    host.flush();
+
+    let start = Instant::now();
+    while host.online_count() > 0 && start.elapsed() < SHUTDOWN_TIMEOUT {
+        if let Some(ENetEventType::Disconnect(mut peer)) = host.service(5) {
+            if let Some(player) = peer.get_data::<Player>() {
+                if player.authenticated && !player.is_guest() && player.dirty {
+                    if let Err(e) = playerdb::save_player(player) {
+                        println!("Failed to save player {}! Error: {:?}", player.name, e);
+                    }
+                }
+            }
+
+            peer.set_data::<Player>(None);
+        }
+    }
+
+    // and again once everyone is gone, for whatever changed while they were leaving
+    let players = save_players(host, false);
+    let worlds = world_manager.save_worlds(false);
+    if players + worlds > 0 {
+        println!("Saved {} worlds and {} players.", worlds, players);
+    }
+
+    println!("Bye!");
 }
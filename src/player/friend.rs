@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+pub const MAX_FRIENDS: usize = 100;
+
+/// Friendships are always saved on both sides, the name is kept so offline friends can be shown without a lookup.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Friend {
+    pub user_id: i32,
+    pub name: String,
+}
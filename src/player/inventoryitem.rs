@@ -2,6 +2,52 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
 pub struct InventoryItem {
-    pub count: u8,
+    pub count: u16,
     pub flags: u8,
 }
+
+/// What the client is told about a stack, it only has a byte for the count. Stacks are capped
+/// at 255 by `ItemInfo::max_count`, so this only matters for counts that aren't in a stack.
+pub fn client_count(count: u16) -> u8 {
+    count.min(u8::MAX as u16) as u8
+}
+
+/// A count change in steps the client can take, each packet only has a byte for it.
+pub fn split_count(count: u16) -> impl Iterator<Item = u8> {
+    let full = count / u8::MAX as u16;
+    let rest = (count % u8::MAX as u16) as u8;
+
+    std::iter::repeat(u8::MAX)
+        .take(full as usize)
+        .chain(Some(rest).filter(|x| *x > 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_count_clamps_to_a_byte() {
+        assert_eq!(client_count(0), 0);
+        assert_eq!(client_count(200), 200);
+        assert_eq!(client_count(255), 255);
+        assert_eq!(client_count(256), 255);
+        assert_eq!(client_count(u16::MAX), 255);
+    }
+
+    #[test]
+    fn split_count_adds_up() {
+        assert_eq!(split_count(0).count(), 0);
+        assert_eq!(split_count(1).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(split_count(255).collect::<Vec<_>>(), vec![255]);
+        assert_eq!(split_count(256).collect::<Vec<_>>(), vec![255, 1]);
+        assert_eq!(split_count(600).collect::<Vec<_>>(), vec![255, 255, 90]);
+
+        for count in [0, 7, 254, 510, 1000, u16::MAX] {
+            assert_eq!(
+                split_count(count).map(|x| x as u32).sum::<u32>(),
+                count as u32
+            );
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+// so nobody can fill up someone else's account
+pub const MAX_MAIL: usize = 50;
+
+/// A private message sent while the player was offline, shown once at their next login.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Mail {
+    pub from: String,
+    pub text: String,
+    pub sent_at: u64, // unix time
+}
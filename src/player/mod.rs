@@ -1,3 +1,8 @@
 pub mod clothing;
+pub mod friend;
 pub mod inventoryitem;
+pub mod mail;
 pub mod player;
+pub mod role;
+pub mod session_manager;
+pub mod trade;
@@ -7,7 +7,9 @@ use crate::{
     item::iteminfo_manager::ITEM_MANAGER,
     utils::variant_function::VariantFunction,
     utils::{
+        self,
         color::Color,
+        dialog::Dialog,
         error::{Error, Result},
     },
     utils::{math::Vec2f, variant_function::VariantFunction::*, variantlist::VariantList},
@@ -22,7 +24,16 @@ use enet_sys::{
     enet_peer_disconnect_later, enet_peer_disconnect_now, enet_peer_send,
 };
 
-use super::{clothing::Clothing, inventoryitem::InventoryItem};
+use super::{
+    clothing::Clothing,
+    friend::Friend,
+    inventoryitem::{self, InventoryItem},
+    mail::Mail,
+    role::Role,
+    trade::Trade,
+};
+
+pub const DEFAULT_ITEMS_SLOTS: u32 = 16;
 
 #[derive(Debug)]
 pub struct Player {
@@ -36,17 +47,22 @@ pub struct Player {
 
     pub char_flags: u32,
     pub items_slots: u32,
+    pub gems: i32,
+    pub role: Role,
 
     pub lmode: u8,
     pub player_age: u8,
     pub platform_id: u8,
 
     pub f: bool,
+    pub pm_muted: bool, // doesn't get private messages at all
+    pub dirty: bool, // set whenever something that gets saved changes, cleared by `playerdb::save_player`
     pub authenticated: bool,
 
     pub rid: String,
     pub name: String,
     pub pass: String,
+    pub pass_hash: String,
     pub gdpr: String,
     pub meta: String,
     pub cbits: String,
@@ -56,6 +72,7 @@ pub struct Player {
     pub current_world: String,
     pub device_version: String,
     pub requested_name: String,
+    pub last_message_from: String, // who `/r` answers to
 
     pub pos: Vec2f,
     pub respawn_pos: Vec2f,
@@ -63,6 +80,15 @@ pub struct Player {
     pub cloth: Clothing,
 
     pub items: HashMap<u16, InventoryItem>,
+
+    pub blocked: Vec<i32>, // user ids that can't send private messages
+    pub mail: Vec<Mail>,
+
+    pub friends: Vec<Friend>,
+    pub friend_requests: Vec<Friend>, // players that asked to be friends since the player logged in
+
+    pub trade: Option<Trade>,
+    pub trade_requests: Vec<i32>, // user ids that asked to trade, like `friend_requests`
 }
 
 impl Default for Player {
@@ -77,18 +103,23 @@ impl Default for Player {
             user_id: -1,
 
             char_flags: 0,
-            items_slots: 16,
+            items_slots: DEFAULT_ITEMS_SLOTS,
+            gems: 0,
+            role: Role::Player,
 
             lmode: 0,
             player_age: 0,
             platform_id: 0,
 
             f: false,
+            pm_muted: false,
+            dirty: false,
             authenticated: false,
 
             rid: String::new(),
             name: String::new(),
             pass: String::new(),
+            pass_hash: String::new(),
             gdpr: String::new(),
             meta: String::new(),
             cbits: String::new(),
@@ -98,6 +129,7 @@ impl Default for Player {
             current_world: String::from("EXIT"),
             device_version: String::new(),
             requested_name: String::new(),
+            last_message_from: String::new(),
 
             pos: Vec2f::new(0.0, 0.0),
             respawn_pos: Vec2f::new(0.0, 0.0),
@@ -105,6 +137,15 @@ impl Default for Player {
             cloth: Clothing::default(),
 
             items: HashMap::new(),
+
+            blocked: Vec::new(),
+            mail: Vec::new(),
+
+            friends: Vec::new(),
+            friend_requests: Vec::new(),
+
+            trade: None,
+            trade_requests: Vec::new(),
         }
     }
 }
@@ -116,8 +157,8 @@ impl Player {
             ..Default::default()
         };
 
-        player.add_item(items::FIST, 1, false).unwrap();
-        player.add_item(items::WRENCH, 1, false).unwrap();
+        player.add_item(items::FIST, 1, false);
+        player.add_item(items::WRENCH, 1, false);
         player.cloth.skin_color = Color::new(0xC3, 0x95, 0x82);
 
         player
@@ -176,6 +217,10 @@ impl Player {
         self.send_varfn_v(varfn, -1, -1);
     }
 
+    pub fn send_dialog(&mut self, dialog: Dialog) {
+        self.send_varfn(OnDialogRequest(&dialog.build()));
+    }
+
     pub fn send_varfn_v(&mut self, varfn: VariantFunction, net_id: i32, delay: i32) {
         if let Ok(varlist) = varfn.serialize() {
             self.send_varlist_v(varlist, net_id, delay);
@@ -247,11 +292,12 @@ impl Player {
                 _ => panic!("Invalid bodypart type: {}!", item.bodypart),
             }
         }
+
+        self.dirty = true;
     }
 
     pub fn display(&self) -> String {
-        let color = "`w";
-        return format!("{}{}``", color, self.name);
+        format!("{}{}``", self.role.prefix(), self.name)
     }
 
     pub fn is_guest(&self) -> bool {
@@ -266,10 +312,70 @@ impl Player {
         Ok(world_manager.get_or_create(&self.current_world, WORLD_TYPE_NORMAL))
     }
 
+    pub fn has_permission(&self, permission: u32) -> bool {
+        self.role.permissions() & permission == permission
+    }
+
+    pub fn is_friend(&self, user_id: i32) -> bool {
+        self.friends.iter().any(|x| x.user_id == user_id)
+    }
+
+    pub fn add_friend(&mut self, user_id: i32, name: &str) {
+        if !self.is_friend(user_id) {
+            self.friends.push(Friend {
+                user_id,
+                name: name.to_string(),
+            });
+            self.dirty = true;
+        }
+    }
+
+    pub fn remove_friend(&mut self, user_id: i32) {
+        self.friends.retain(|x| x.user_id != user_id);
+        self.dirty = true;
+    }
+
+    pub fn accepts_messages_from(&self, user_id: i32) -> bool {
+        !self.pm_muted && !self.blocked.contains(&user_id)
+    }
+
+    /// `world` is left out if empty.
+    pub fn send_private_message(&mut self, from: &str, world: &str, text: &str) {
+        let world = if world.is_empty() || world == "EXIT" {
+            String::new()
+        } else {
+            format!(" in [`4{}`c]", world)
+        };
+
+        self.send_varfn(OnConsoleMessage(&format!(
+            "CP:0_PL:4_OID:_CT:[MSG]_ `c>> from ({}`c){} > `${}``",
+            from, world, text
+        )));
+    }
+
+    /// Shows everything that was sent while the player was offline, once.
+    pub fn send_mail(&mut self) {
+        if self.mail.is_empty() {
+            return;
+        }
+
+        self.send_log(&format!(
+            "`5You got {} messages while you were away:``",
+            self.mail.len()
+        ));
+
+        let now = utils::unix_time();
+        for mail in std::mem::take(&mut self.mail) {
+            let ago = utils::format_duration(now.saturating_sub(mail.sent_at));
+            self.send_private_message(&format!("`w{}``", mail.from), "", &mail.text);
+            self.send_log(&format!("`5(sent {} ago)``", ago));
+        }
+
+        self.dirty = true;
+    }
+
     pub fn get_chat_color(&self) -> String {
-        let color = String::new();
-        // later used
-        color
+        self.role.chat_color().to_string()
     }
 
     pub fn get_spawn_info(&self, local: bool) -> String {
@@ -280,7 +386,7 @@ impl Player {
                 "userID|{}\n",
                 "colrect|0|0|20|30\n",
                 "posXY|{}|{}\n",
-                "name|``{}\n",
+                "name|{}\n",
                 "country|{}\n",
                 "invis|{}\n",
                 "mstate|{}\n",
@@ -291,7 +397,7 @@ impl Player {
             self.user_id,
             self.pos.x,
             self.pos.y,
-            self.name,
+            self.display(),
             self.country_code,
             0,
             0,
@@ -308,6 +414,23 @@ impl Player {
         self.items_slots <= count + self.items.len() as u32
     }
 
+    pub fn get_item_count(&self, id: u16) -> u16 {
+        self.items.get(&id).map_or(0, |item| item.count)
+    }
+
+    /// How many more of `id` fit in the backpack.
+    pub fn item_room(&self, id: u16) -> u16 {
+        let max_count = ITEM_MANAGER
+            .get_item_safe(id as u32)
+            .map_or(0, |item| item.max_count());
+
+        match self.items.get(&id) {
+            Some(item) => max_count.saturating_sub(item.count),
+            None if self.items_full() => 0,
+            None => max_count,
+        }
+    }
+
     pub fn has_item(&self, id: u16) -> bool {
         match self.items.get(&id) {
             Some(_) => true,
@@ -315,39 +438,28 @@ impl Player {
         }
     }
 
-    pub fn add_item(&mut self, id: u16, count: u8, visual: bool) -> Result<()> {
-        assert_ne!(count, 0);
-
-        if count > consts::MAXIMUM_ITEMS_COUNT {
-            return Err(Error::TooManyItemError);
+    /// Adds as many as fit and returns how many didn't, so the caller can drop them into the world.
+    pub fn add_item(&mut self, id: u16, count: u16, visual: bool) -> u16 {
+        let added = self.item_room(id).min(count);
+        if added == 0 {
+            return count;
         }
 
-        match self.items.get_mut(&id) {
-            Some(item) => {
-                if item.count as usize + count as usize > consts::MAXIMUM_ITEMS_COUNT as usize {
-                    return Err(Error::TooManyItemError);
-                }
-
-                item.count += count;
-            }
+        self.items
+            .entry(id)
+            .or_insert(InventoryItem { count: 0, flags: 0 })
+            .count += added;
 
-            None => {
-                if self.items_full() {
-                    return Err(Error::InventoryFullError);
-                }
-
-                self.items.insert(id, InventoryItem { count, flags: 0 });
-            }
-        }
+        self.dirty = true;
 
         if visual {
-            self.send_modify_inventory(id, count, true);
+            self.send_modify_inventory(id, added, true);
         }
 
-        Ok(())
+        count - added
     }
 
-    pub fn remove_item(&mut self, id: u16, count: u8, visual: bool) -> Result<()> {
+    pub fn remove_item(&mut self, id: u16, count: u16, visual: bool) -> Result<()> {
         match self.items.get_mut(&id) {
             Some(item) => {
                 let difference = item.count as i32 - count as i32;
@@ -363,6 +475,8 @@ impl Player {
             None => return Err(Error::ItemNotFound),
         }
 
+        self.dirty = true;
+
         if visual {
             self.send_modify_inventory(id, count, false);
         }
@@ -371,7 +485,7 @@ impl Player {
     }
 
     pub fn remove_item_all(&mut self, id: u16, visual: bool) -> Result<()> {
-        let count: u8;
+        let count: u16;
         match self.items.get(&id) {
             Some(item) => {
                 count = item.count;
@@ -380,6 +494,8 @@ impl Player {
             None => return Err(Error::ItemNotFound),
         }
 
+        self.dirty = true;
+
         if visual {
             self.send_modify_inventory(id, count, false);
         }
@@ -387,6 +503,25 @@ impl Player {
         Ok(())
     }
 
+    pub fn add_gems(&mut self, amount: i32) {
+        self.set_gems(self.gems.saturating_add(amount));
+    }
+
+    pub fn remove_gems(&mut self, amount: i32) -> Result<()> {
+        if self.gems < amount {
+            return Err(Error::NotEnoughGems);
+        }
+
+        self.set_gems(self.gems - amount);
+        Ok(())
+    }
+
+    pub fn set_gems(&mut self, gems: i32) {
+        self.gems = gems;
+        self.dirty = true;
+        self.send_varfn(OnSetBux(gems));
+    }
+
     pub fn send_inventory(&mut self) -> Result<()> {
         let mut data = Vec::<u8>::with_capacity(self.items.len() * 20);
         data.write_u8(1)?;
@@ -395,7 +530,7 @@ impl Player {
 
         for (id, item) in self.items.iter() {
             data.write_u16::<LE>(*id as u16)?;
-            data.write_u8(item.count)?;
+            data.write_u8(inventoryitem::client_count(item.count))?;
             data.write_u8(item.flags)?;
         }
 
@@ -406,17 +541,20 @@ impl Player {
         Ok(())
     }
 
-    pub fn send_modify_inventory(&mut self, id: u16, count: u8, add: bool) {
-        let mut tankpacket = TankUpdatePacket::default();
-        tankpacket.int_val = id as i32;
-        tankpacket.packet_type = packet_type::MODIFY_ITEM_INVENTORY;
+    /// Split into several packets if needed, each can only change the count by 255.
+    pub fn send_modify_inventory(&mut self, id: u16, count: u16, add: bool) {
+        for part in inventoryitem::split_count(count) {
+            let mut tankpacket = TankUpdatePacket::default();
+            tankpacket.int_val = id as i32;
+            tankpacket.packet_type = packet_type::MODIFY_ITEM_INVENTORY;
 
-        if add {
-            tankpacket.field3 = count;
-        } else {
-            tankpacket.field2 = count;
-        }
+            if add {
+                tankpacket.field3 = part;
+            } else {
+                tankpacket.field2 = part;
+            }
 
-        self.send_tankpacket(tankpacket);
+            self.send_tankpacket(tankpacket);
+        }
     }
 }
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::consts::permissions;
+
+/// Saved with the account, decides what a player may do and how their name looks.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Player,
+    Vip,
+    Moderator,
+    Admin,
+    Developer,
+}
+
+impl Role {
+    pub const ALL: [Role; 5] = [
+        Role::Player,
+        Role::Vip,
+        Role::Moderator,
+        Role::Admin,
+        Role::Developer,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Player => "player",
+            Role::Vip => "vip",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+            Role::Developer => "developer",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Role> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Goes in front of the name everywhere it's shown, color included.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Role::Player => "`w",
+            Role::Vip => "`w[`1VIP`w] ",
+            Role::Moderator => "`#@",
+            Role::Admin => "`e@",
+            Role::Developer => "`6@",
+        }
+    }
+
+    pub fn chat_color(&self) -> &'static str {
+        match self {
+            Role::Player => "",
+            Role::Vip => "`1",
+            Role::Moderator => "`^",
+            Role::Admin => "`e",
+            Role::Developer => "`5",
+        }
+    }
+
+    pub fn permissions(&self) -> u32 {
+        match self {
+            Role::Player | Role::Vip => permissions::NONE,
+            Role::Moderator => permissions::BYPASS_LOCKS | permissions::KICK | permissions::MUTE,
+            Role::Admin => {
+                Role::Moderator.permissions() | permissions::BAN | permissions::GIVE_ITEMS
+            }
+            Role::Developer => permissions::ALL,
+        }
+    }
+}
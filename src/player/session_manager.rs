@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use enet_sys::_ENetPeer;
+
+use super::player::Player;
+
+/// Every authenticated player that is online, so players in other worlds can be found.
+/// Guests and players still logging in aren't in here.
+pub struct SessionManager {
+    peers: HashMap<i32, *mut _ENetPeer>, // by user id
+    user_ids: HashMap<String, i32>,      // by lowercase name
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        Self {
+            peers: HashMap::new(),
+            user_ids: HashMap::new(),
+        }
+    }
+
+    /// Takes over the account from an older session, which should be kicked first.
+    pub fn register(&mut self, player: &Player) {
+        self.user_ids
+            .insert(player.name.to_lowercase(), player.user_id);
+        self.peers.insert(player.user_id, player.inner_peer);
+    }
+
+    /// Does nothing if a newer session of the same account took over already.
+    pub fn unregister(&mut self, player: &Player) {
+        if self.peers.get(&player.user_id) != Some(&player.inner_peer) {
+            return;
+        }
+
+        self.peers.remove(&player.user_id);
+        self.user_ids.remove(&player.name.to_lowercase());
+    }
+
+    pub fn count(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_online(&self, user_id: i32) -> bool {
+        self.peers.contains_key(&user_id)
+    }
+
+    pub fn find_user_id(&self, name: &str) -> Option<i32> {
+        self.user_ids.get(&name.to_lowercase()).copied()
+    }
+
+    /// `except` is the player the caller already holds, it's never returned so it can't be borrowed twice.
+    pub fn get<'a>(&'a mut self, user_id: i32, except: Option<&Player>) -> Option<&'a mut Player> {
+        let peer = *self.peers.get(&user_id)?;
+        if except.is_some_and(|x| x.inner_peer == peer) {
+            return None;
+        }
+
+        unsafe { ((*peer).data as *mut Player).as_mut() }
+    }
+
+    pub fn get_by_name<'a>(
+        &'a mut self,
+        name: &str,
+        except: Option<&Player>,
+    ) -> Option<&'a mut Player> {
+        self.get(self.find_user_id(name)?, except)
+    }
+
+    /// Sends something like a login alert to every friend of `player` that is online.
+    pub fn notify_friends(&mut self, player: &Player, message: &str) {
+        for friend in player.friends.iter() {
+            if let Some(friend) = self.get(friend.user_id, Some(player)) {
+                friend.send_log(message);
+            }
+        }
+    }
+
+    pub fn players<'a>(
+        &'a mut self,
+        except: Option<&Player>,
+    ) -> impl Iterator<Item = &'a mut Player> + 'a {
+        let except = except.map(|x| x.inner_peer);
+        self.peers
+            .values()
+            .filter(move |peer| Some(**peer) != except)
+            .filter_map(|peer| unsafe { ((**peer).data as *mut Player).as_mut() })
+    }
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
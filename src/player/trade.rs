@@ -0,0 +1,266 @@
+use crate::item::iteminfo_manager::ITEM_MANAGER;
+
+use super::{player::Player, session_manager::SessionManager};
+
+pub const MAX_TRADE_ITEMS: usize = 4;
+
+/// One side of a trade, both players hold their own with the other one as `partner`.
+#[derive(Clone, Debug)]
+pub struct Trade {
+    pub partner: i32, // user id
+    pub world: String,
+    pub items: Vec<(u16, u16)>,
+    pub gems: i32,
+    pub accepted: bool,
+    pub confirmed: bool, // second step, only possible once both accepted
+}
+
+impl Trade {
+    pub fn new(partner: i32, world: &str) -> Self {
+        Self {
+            partner,
+            world: world.to_owned(),
+            items: Vec::new(),
+            gems: 0,
+            accepted: false,
+            confirmed: false,
+        }
+    }
+
+    pub fn get_count(&self, id: u16) -> u16 {
+        self.items
+            .iter()
+            .find(|(x, _)| *x == id)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    pub fn reset(&mut self) {
+        self.accepted = false;
+        self.confirmed = false;
+    }
+
+    /// The second step, only counts once both accepted. Whether both sides confirmed now.
+    pub fn confirm(&mut self, theirs: &Trade) -> bool {
+        if self.accepted && theirs.accepted {
+            self.confirmed = true;
+        }
+
+        self.confirmed && theirs.confirmed
+    }
+}
+
+/// The other side of the trade, only if it's still going and both are in the world it started in.
+pub fn get_partner<'a>(
+    player: &Player,
+    sessions: &'a mut SessionManager,
+) -> Option<&'a mut Player> {
+    let trade = player.trade.as_ref()?;
+    if player.current_world != trade.world {
+        return None;
+    }
+
+    sessions
+        .get(trade.partner, Some(player))
+        .filter(|partner| partner.current_world == trade.world)
+        .filter(|partner| {
+            partner
+                .trade
+                .as_ref()
+                .is_some_and(|x| x.partner == player.user_id)
+        })
+}
+
+/// Ends the trade on both sides, `reason` is shown to the partner. Also used on disconnect, so `player` isn't told.
+pub fn cancel(player: &mut Player, sessions: &mut SessionManager, reason: &str) {
+    let trade = match player.trade.take() {
+        Some(trade) => trade,
+        None => return,
+    };
+
+    if let Some(partner) = sessions.get(trade.partner, Some(player)) {
+        if partner
+            .trade
+            .as_ref()
+            .is_some_and(|x| x.partner == player.user_id)
+        {
+            partner.trade = None;
+            partner.send_log(&format!("`4Trade cancelled:`` {}", reason));
+        }
+    }
+}
+
+/// Whether `player` still has everything they offered.
+pub fn can_give(player: &Player, trade: &Trade) -> bool {
+    player.gems >= trade.gems
+        && trade
+            .items
+            .iter()
+            .all(|(id, count)| player.get_item_count(*id) >= *count)
+}
+
+/// Whether everything in `gets` fits once `gives` left the backpack, both in slots and per item.
+pub fn can_receive(player: &Player, gives: &Trade, gets: &Trade) -> bool {
+    fits(player, gives, gets, |id| {
+        ITEM_MANAGER
+            .get_item_safe(id as u32)
+            .map_or(0, |item| item.max_count())
+    })
+}
+
+fn fits(player: &Player, gives: &Trade, gets: &Trade, max_count: impl Fn(u16) -> u16) -> bool {
+    let mut slots = player.items.len();
+    for (id, count) in gives.items.iter() {
+        if player.get_item_count(*id) == *count {
+            slots -= 1;
+        }
+    }
+
+    for (id, count) in gets.items.iter() {
+        let left = player
+            .get_item_count(*id)
+            .saturating_sub(gives.get_count(*id));
+        if left as u32 + *count as u32 > max_count(*id) as u32 {
+            return false;
+        }
+
+        if left == 0 {
+            slots += 1;
+        }
+    }
+
+    slots <= player.items_slots as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::player::inventoryitem::InventoryItem;
+
+    use super::*;
+
+    #[test]
+    fn can_give_only_what_is_there() {
+        let player = Player {
+            items: HashMap::from([
+                (
+                    2,
+                    InventoryItem {
+                        count: 10,
+                        flags: 0,
+                    },
+                ),
+                (4, InventoryItem { count: 1, flags: 0 }),
+            ]),
+            gems: 50,
+            ..Default::default()
+        };
+        let trade = Trade::new(1, "TEST");
+
+        let everything = Trade {
+            items: vec![(2, 10), (4, 1)],
+            gems: 50,
+            ..trade.clone()
+        };
+        assert!(can_give(&player, &everything));
+
+        for items in [vec![(2, 11)], vec![(6, 1)]] {
+            assert!(!can_give(
+                &player,
+                &Trade {
+                    items,
+                    ..trade.clone()
+                }
+            ));
+        }
+
+        assert!(!can_give(&player, &Trade { gems: 51, ..trade }));
+    }
+
+    #[test]
+    fn receiving_respects_the_stack_limit() {
+        let player = Player {
+            items: HashMap::from([(
+                2,
+                InventoryItem {
+                    count: 150,
+                    flags: 0,
+                },
+            )]),
+            ..Default::default()
+        };
+        let nothing = Trade::new(1, "TEST");
+        let max_count = |_| 200;
+
+        let gets = |count| Trade {
+            items: vec![(2, count)],
+            ..nothing.clone()
+        };
+        assert!(fits(&player, &nothing, &gets(50), max_count));
+        assert!(!fits(&player, &nothing, &gets(51), max_count));
+
+        // what is given away makes room first
+        assert!(fits(&player, &gets(100), &gets(150), max_count));
+    }
+
+    #[test]
+    fn receiving_respects_the_slots() {
+        let player = Player {
+            items: HashMap::from([
+                (2, InventoryItem { count: 1, flags: 0 }),
+                (4, InventoryItem { count: 1, flags: 0 }),
+            ]),
+            items_slots: 2,
+            ..Default::default()
+        };
+        let nothing = Trade::new(1, "TEST");
+        let offer = |items: &[(u16, _)]| Trade {
+            items: items.to_vec(),
+            ..nothing.clone()
+        };
+        let max_count = |_| 200;
+
+        assert!(fits(&player, &nothing, &offer(&[(2, 5)]), max_count));
+        assert!(!fits(&player, &nothing, &offer(&[(6, 1)]), max_count));
+
+        // giving a whole stack away frees its slot
+        let gives = offer(&[(4, 1)]);
+        assert!(fits(&player, &gives, &offer(&[(6, 1)]), max_count));
+        assert!(!fits(&player, &gives, &offer(&[(6, 1), (8, 1)]), max_count));
+    }
+
+    #[test]
+    fn confirm_needs_both_to_accept_first() {
+        let mut ours = Trade::new(1, "TEST");
+        let mut theirs = Trade::new(2, "TEST");
+
+        assert!(!ours.confirm(&theirs));
+        assert!(!ours.confirmed);
+
+        ours.accepted = true;
+        assert!(!ours.confirm(&theirs));
+        assert!(!ours.confirmed);
+
+        theirs.accepted = true;
+        assert!(!ours.confirm(&theirs));
+        assert!(ours.confirmed);
+        assert!(theirs.confirm(&ours));
+    }
+
+    #[test]
+    fn reset_takes_back_both_steps() {
+        let mut trade = Trade {
+            items: vec![(2, 1)],
+            accepted: true,
+            confirmed: true,
+            ..Trade::new(1, "TEST")
+        };
+
+        trade.reset();
+
+        assert!(!trade.accepted);
+        assert!(!trade.confirmed);
+        assert_eq!(trade.get_count(2), 1);
+        assert_eq!(trade.get_count(4), 0);
+    }
+}
@@ -0,0 +1,144 @@
+use std::time::{Duration, Instant};
+
+use enet_sys::_ENetPeer;
+
+use crate::{
+    enet_wrapper::{host::ENetHost, peer::ENetPeer},
+    player::player::Player,
+    utils::error::Result,
+    world::world_manager::WorldManager,
+};
+
+use super::task::{Task, TaskContext, TaskId};
+
+pub struct Scheduler {
+    tasks: Vec<Task>,
+    last_task_id: TaskId,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            last_task_id: 0,
+        }
+    }
+
+    pub fn schedule_once<F>(&mut self, delay: Duration, callback: F) -> TaskId
+    where
+        F: FnMut(&mut TaskContext) -> Result<()> + 'static,
+    {
+        self.push(delay, None, None, callback)
+    }
+
+    pub fn schedule_repeating<F>(&mut self, interval: Duration, callback: F) -> TaskId
+    where
+        F: FnMut(&mut TaskContext) -> Result<()> + 'static,
+    {
+        self.push(interval, Some(interval), None, callback)
+    }
+
+    /// Same as `schedule_once`, but `TaskContext::player` will be set to the given player.
+    /// The task is dropped if the player disconnects before it runs.
+    pub fn schedule_for_player<F>(&mut self, player: &Player, delay: Duration, callback: F) -> TaskId
+    where
+        F: FnMut(&mut TaskContext) -> Result<()> + 'static,
+    {
+        self.push(delay, None, Some(player.inner_peer), callback)
+    }
+
+    pub fn schedule_repeating_for_player<F>(
+        &mut self,
+        player: &Player,
+        interval: Duration,
+        callback: F,
+    ) -> TaskId
+    where
+        F: FnMut(&mut TaskContext) -> Result<()> + 'static,
+    {
+        self.push(interval, Some(interval), Some(player.inner_peer), callback)
+    }
+
+    pub fn cancel(&mut self, id: TaskId) -> bool {
+        let len = self.tasks.len();
+        self.tasks.retain(|task| task.id != id);
+        len != self.tasks.len()
+    }
+
+    /// Must be called when a peer disconnects, ENet reuses peers so the tasks would
+    /// otherwise run against whoever gets the peer next.
+    pub fn cancel_for_peer(&mut self, peer: *mut _ENetPeer) {
+        self.tasks.retain(|task| task.owner != Some(peer));
+    }
+
+    pub fn is_scheduled(&self, id: TaskId) -> bool {
+        self.tasks.iter().any(|task| task.id == id)
+    }
+
+    pub fn tick(&mut self, host: &ENetHost, world_manager: &mut WorldManager) {
+        let now = Instant::now();
+        if !self.tasks.iter().any(|task| task.is_due(now)) {
+            return;
+        }
+
+        let (due, pending): (Vec<Task>, Vec<Task>) = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .partition(|task| task.is_due(now));
+
+        self.tasks = pending;
+
+        for mut task in due {
+            let mut owner = task.owner.map(ENetPeer::new);
+            let player = match owner.as_mut() {
+                Some(peer) => match peer.get_data::<Player>() {
+                    Some(player) => Some(player),
+                    None => continue, // player is gone, so is the task
+                },
+                None => None,
+            };
+
+            let mut ctx = TaskContext {
+                host,
+                world_manager: &mut *world_manager,
+                player,
+            };
+
+            if let Err(e) = (task.callback)(&mut ctx) {
+                println!("Scheduled task {} failed! Error: {:?}", task.id, e);
+            }
+
+            if let Some(interval) = task.interval {
+                // dont try to catch up if the server was stalled, just run it again later
+                task.next_run += interval;
+                if task.next_run <= now {
+                    task.next_run = now + interval;
+                }
+
+                self.tasks.push(task);
+            }
+        }
+    }
+
+    fn push<F>(
+        &mut self,
+        delay: Duration,
+        interval: Option<Duration>,
+        owner: Option<*mut _ENetPeer>,
+        callback: F,
+    ) -> TaskId
+    where
+        F: FnMut(&mut TaskContext) -> Result<()> + 'static,
+    {
+        self.last_task_id += 1;
+
+        self.tasks.push(Task {
+            id: self.last_task_id,
+            next_run: Instant::now() + delay,
+            interval,
+            owner,
+            callback: Box::new(callback),
+        });
+
+        self.last_task_id
+    }
+}
@@ -0,0 +1,33 @@
+use std::time::{Duration, Instant};
+
+use enet_sys::_ENetPeer;
+
+use crate::{
+    enet_wrapper::host::ENetHost, player::player::Player, utils::error::Result,
+    world::world_manager::WorldManager,
+};
+
+pub type TaskId = u64;
+pub type TaskCallback = Box<dyn FnMut(&mut TaskContext) -> Result<()>>;
+
+pub struct TaskContext<'a> {
+    pub host: &'a ENetHost,
+    pub world_manager: &'a mut WorldManager,
+
+    // only set for tasks scheduled on behalf of a player
+    pub player: Option<&'a mut Player>,
+}
+
+pub struct Task {
+    pub id: TaskId,
+    pub next_run: Instant,
+    pub interval: Option<Duration>,
+    pub owner: Option<*mut _ENetPeer>,
+    pub callback: TaskCallback,
+}
+
+impl Task {
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.next_run <= now
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt::Display;
+
+pub enum Size {
+    Small,
+    Big,
+}
+
+impl Size {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Size::Small => "small",
+            Size::Big => "big",
+        }
+    }
+}
+
+/// Builds the text for `OnDialogRequest`, every value is cleaned so players can't inject their own elements.
+///
+/// ```ignore
+/// let dialog = Dialog::new()
+///     .add_label_with_icon(Size::Big, "Hello", items::SIGN)
+///     .add_text_input("text", "Text:", "", 100)
+///     .end_dialog("hello", "Cancel", "OK");
+///
+/// player.send_dialog(dialog);
+/// ```
+pub struct Dialog {
+    data: String,
+}
+
+impl Dialog {
+    pub fn new() -> Self {
+        Self {
+            data: String::from("set_default_color|`o\n"),
+        }
+    }
+
+    pub fn add_label(self, size: Size, text: &str) -> Self {
+        self.add(&format!(
+            "add_label|{}|{}|left|",
+            size.as_str(),
+            clean(text)
+        ))
+    }
+
+    pub fn add_label_with_icon(self, size: Size, text: &str, icon: u16) -> Self {
+        self.add(&format!(
+            "add_label_with_icon|{}|{}|left|{}|",
+            size.as_str(),
+            clean(text),
+            icon
+        ))
+    }
+
+    pub fn add_textbox(self, text: &str) -> Self {
+        self.add(&format!("add_textbox|{}|left|", clean(text)))
+    }
+
+    pub fn add_smalltext(self, text: &str) -> Self {
+        self.add(&format!("add_smalltext|{}|", clean(text)))
+    }
+
+    pub fn add_text_input(self, name: &str, label: &str, value: &str, max_len: usize) -> Self {
+        self.add(&format!(
+            "add_text_input|{}|{}|{}|{}|",
+            clean(name),
+            clean(label),
+            clean(value),
+            max_len
+        ))
+    }
+
+    pub fn add_password_input(self, name: &str, label: &str, max_len: usize) -> Self {
+        self.add(&format!(
+            "add_text_input_password|{}|{}||{}|",
+            clean(name),
+            clean(label),
+            max_len
+        ))
+    }
+
+    pub fn add_button(self, name: &str, text: &str) -> Self {
+        self.add(&format!(
+            "add_button|{}|{}|noflags|0|0|",
+            clean(name),
+            clean(text)
+        ))
+    }
+
+    pub fn add_url_button(self, text: &str, url: &str, prompt: &str) -> Self {
+        self.add(&format!(
+            "add_url_button||{}|NOFLAGS|{}|{}|0|0|",
+            clean(text),
+            clean(url),
+            clean(prompt)
+        ))
+    }
+
+    /// Lets the player pick something from their backpack, the item id comes back as `name`.
+    pub fn add_item_picker(self, name: &str, label: &str, prompt: &str) -> Self {
+        self.add(&format!(
+            "add_item_picker|{}|{}|{}|",
+            clean(name),
+            clean(label),
+            clean(prompt)
+        ))
+    }
+
+    pub fn add_checkbox(self, name: &str, label: &str, checked: bool) -> Self {
+        self.add(&format!(
+            "add_checkbox|{}|{}|{}|",
+            clean(name),
+            clean(label),
+            checked as u8
+        ))
+    }
+
+    pub fn add_spacer(self, size: Size) -> Self {
+        self.add(&format!("add_spacer|{}|", size.as_str()))
+    }
+
+    /// Sent back untouched in the `dialog_return`.
+    pub fn embed_data<T: Display>(self, name: &str, value: T) -> Self {
+        self.add(&format!(
+            "embed_data|{}|{}",
+            clean(name),
+            clean(&value.to_string())
+        ))
+    }
+
+    pub fn add_quick_exit(self) -> Self {
+        self.add("add_quick_exit")
+    }
+
+    /// `name` is the `dialog_name` the answer gets routed by, an empty `cancel` or `ok` hides that button.
+    pub fn end_dialog(self, name: &str, cancel: &str, ok: &str) -> Self {
+        self.add(&format!(
+            "end_dialog|{}|{}|{}|",
+            clean(name),
+            clean(cancel),
+            clean(ok)
+        ))
+    }
+
+    pub fn build(self) -> String {
+        self.data
+    }
+
+    fn add(mut self, line: &str) -> Self {
+        self.data.push_str(line);
+        self.data.push('\n');
+        self
+    }
+}
+
+impl Default for Dialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Strips everything that would end the current element.
+pub fn clean(text: &str) -> String {
+    text.chars()
+        .filter(|x| !matches!(x, '|' | '\n' | '\r'))
+        .collect()
+}
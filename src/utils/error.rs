@@ -1,5 +1,7 @@
 use std::{fmt, io, num::ParseIntError, result, str::Utf8Error};
 
+use argon2::password_hash;
+
 pub type Result<T> = result::Result<T, Error>;
 
 #[derive(Debug)]
@@ -14,11 +16,17 @@ pub enum Error {
     Disconnected,
     ItemNotFound,
     WrongPassword,
-    TooManyItemError,
     NameAlreadyExists,
     ItemCountNegative,
-    InventoryFullError,
+    NotEnoughGems,
     InvalidPacketError,
+    NotFound,
+    CorruptedData,
+    UnsupportedVersion(u32),
+    ConfigError(String),
+    CommandError(String), // shown to the player together with the usage
+    SqliteError(rusqlite::Error),
+    PasswordHashError(password_hash::Error),
 }
 
 impl From<io::Error> for Error {
@@ -45,10 +53,20 @@ impl From<ParseIntError> for Error {
     }
 }
 
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::SqliteError(e)
+    }
+}
+
+impl From<password_hash::Error> for Error {
+    fn from(e: password_hash::Error) -> Self {
+        Self::PasswordHashError(e)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
-        let message = self.to_string();
-
-        f.write_fmt(format_args!("Error {{ message: {} }}", message))
+        f.write_fmt(format_args!("Error {{ message: {:?} }}", self))
     }
 }
@@ -0,0 +1,68 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Writes to a temporary file first and renames it over `path` once everything is on disk,
+/// so a crash in the middle of a write never leaves a truncated file behind.
+/// The previous content of `path` is kept in up to `backups` rotating backup files.
+pub fn write_atomic(path: &Path, data: &[u8], backups: usize) -> io::Result<()> {
+    let tmp_path = with_suffix(path, ".tmp");
+
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+
+    if backups > 0 && path.exists() {
+        rotate_backups(path, backups)?;
+    }
+
+    fs::rename(&tmp_path, path)?;
+    sync_parent(path)
+}
+
+/// `<path>.bak1` is the newest backup, `<path>.bak<backups>` the oldest.
+pub fn backup_path(path: &Path, index: usize) -> PathBuf {
+    with_suffix(path, &format!(".bak{}", index))
+}
+
+/// Existing backups of `path`, newest first.
+pub fn backups(path: &Path, backups: usize) -> Vec<PathBuf> {
+    (1..=backups)
+        .map(|i| backup_path(path, i))
+        .filter(|x| x.exists())
+        .collect()
+}
+
+fn rotate_backups(path: &Path, backups: usize) -> io::Result<()> {
+    for i in (1..backups).rev() {
+        let from = backup_path(path, i);
+        if from.exists() {
+            fs::rename(from, backup_path(path, i + 1))?;
+        }
+    }
+
+    fs::rename(path, backup_path(path, 1))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// the rename itself is only durable once the directory entry is flushed
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => File::open(parent)?.sync_all(),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_: &Path) -> io::Result<()> {
+    Ok(())
+}
@@ -1,4 +1,4 @@
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Vec2<T> {
     pub x: T,
     pub y: T,
@@ -10,7 +10,13 @@ impl<T> Vec2<T> {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl Vec2<f32> {
+    pub fn distance(&self, other: &Self) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Vec3<T> {
     pub x: T,
     pub y: T,
@@ -1,11 +1,16 @@
 pub mod color;
+pub mod dialog;
 pub mod error;
+pub mod file;
 pub mod math;
 pub mod mem;
+pub mod password;
 pub mod variant;
 pub mod variant_function;
 pub mod variantlist;
 
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use rand::{
     distributions::uniform::{SampleRange, SampleUniform},
     Rng,
@@ -21,6 +26,66 @@ pub fn hash(data: &[u8]) -> u32 {
     hash
 }
 
+/// Seconds since the unix epoch, used for everything that has to survive a restart.
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs())
+}
+
+/// Human readable, only the two biggest units, e.g. "2 days 3 hours".
+pub fn format_duration(secs: u64) -> String {
+    const UNITS: [(u64, &str); 4] = [
+        (86400, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ];
+
+    let mut parts = Vec::new();
+    let mut rest = secs;
+    for (size, name) in UNITS.iter() {
+        let count = rest / size;
+        rest %= size;
+
+        if count > 0 {
+            parts.push(format!(
+                "{} {}{}",
+                count,
+                name,
+                if count == 1 { "" } else { "s" }
+            ));
+        }
+
+        if parts.len() == 2 {
+            break;
+        }
+    }
+
+    if parts.is_empty() {
+        return "0 seconds".to_string();
+    }
+
+    parts.join(" ")
+}
+
+/// `30s`, `15m`, `12h`, `7d` or `2w` to seconds.
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let unit = text.chars().last()?;
+    let count = text[..text.len() - unit.len_utf8()].parse::<u64>().ok()?;
+
+    let size = match unit.to_ascii_lowercase() {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        'w' => 604800,
+        _ => return None,
+    };
+
+    count.checked_mul(size).filter(|x| *x > 0)
+}
+
 pub fn random<T, R>(range: R) -> T
 where
     T: SampleUniform,
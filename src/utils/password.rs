@@ -0,0 +1,30 @@
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use rand::RngCore;
+
+use super::error::Result;
+
+pub fn hash(pass: &str) -> Result<String> {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+
+    let salt = SaltString::encode_b64(&salt)?;
+    let hash = Argon2::default().hash_password(pass.as_bytes(), &salt)?;
+
+    Ok(hash.to_string())
+}
+
+pub fn verify(pass: &str, hash: &str) -> Result<bool> {
+    let hash = PasswordHash::new(hash)?;
+
+    Ok(Argon2::default()
+        .verify_password(pass.as_bytes(), &hash)
+        .is_ok())
+}
+
+/// Accounts created before passwords were hashed still have them stored in plaintext.
+pub fn is_hashed(stored: &str) -> bool {
+    stored.starts_with("$argon2")
+}
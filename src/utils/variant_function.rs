@@ -1,4 +1,7 @@
-use crate::{item::iteminfo_manager::ITEM_MANAGER, player::clothing::Clothing, utils::math::Vec3f};
+use crate::{
+    config::config::CONFIG, item::iteminfo_manager::ITEM_MANAGER, player::clothing::Clothing,
+    utils::math::Vec3f,
+};
 
 use super::{math::Vec2f, variantlist::VariantList};
 
@@ -17,6 +20,8 @@ pub enum VariantFunction<'a> {
     OnSetPos(Vec2f),
     OnPlayPositioned(&'a str),
     SetHasGrowID(bool, &'a str, &'a str),
+    OnSetBux(i32),
+    OnNameChanged(&'a str),
 }
 
 impl<'a> VariantFunction<'a> {
@@ -31,8 +36,8 @@ impl<'a> VariantFunction<'a> {
             Self::OnSuperMain => {
                 varlist.push("OnSuperMainStartAcceptLogonHrdxs47254722215a");
                 varlist.push(ITEM_MANAGER.hash);
-                varlist.push("ubistatic-a.akamaihd.net");
-                varlist.push("0098/67197/cache/");
+                varlist.push(&CONFIG.cdn_host);
+                varlist.push(&CONFIG.cdn_path);
                 varlist.push( concat!(
                     "cc.cz.madkite.freedom org.aqua.gg idv.aqua.bulldog com.cih.gamecih2 com.cih.gamecih com.cih.game_cih cn.maocai.gamekiller ",
                     "com.gmd.speedtime org.dax.attack com.x0.strai.frep com.x0.strai.free org.cheatengine.cegui org.sbtools.gamehack ",
@@ -129,6 +134,17 @@ impl<'a> VariantFunction<'a> {
                 varlist.push(name);
                 varlist.push(pass);
             }
+
+            Self::OnSetBux(gems) => {
+                varlist.push("OnSetBux");
+                varlist.push(gems);
+                varlist.push(1); // idk whats this
+            }
+
+            Self::OnNameChanged(name) => {
+                varlist.push("OnNameChanged");
+                varlist.push(name);
+            }
         }
 
         Ok(varlist)
@@ -3,10 +3,9 @@ use std::{cell::RefCell, rc::Weak, time::Instant};
 use byteorder::{WriteBytesExt, LE};
 
 use crate::{
-    consts::{self, items, tile_flags},
+    consts::{self, item_type, items, tile_flags},
     item::{iteminfo::ItemInfo, iteminfo_manager::ITEM_MANAGER},
-    player::player::Player,
-    utils::{error::Result, math::Vec2u},
+    utils::{self, error::Result, math::Vec2u},
 };
 
 use super::{tileextra::TileExtra, world::World};
@@ -80,9 +79,25 @@ impl Tile {
         (self.flags & flags) != 0
     }
 
+    pub fn is_tree(&self) -> bool {
+        self.has_flag(tile_flags::SEED)
+    }
+
+    /// Whether the tree on this tile is fully grown, based on when it was planted.
+    pub fn is_ripe(&self) -> Result<bool> {
+        match self.extra {
+            TileExtra::Seed { planted_at, .. } if self.is_tree() => {
+                let tree_time = self.get_fore()?.tree_time as u64;
+                Ok(planted_at + tree_time <= utils::unix_time())
+            }
+            _ => Ok(false),
+        }
+    }
+
     pub fn remove_base(&mut self) {
         if self.fore != items::BLANK {
             self.fore = items::BLANK;
+            self.extra = TileExtra::None;
         } else {
             self.back = items::BLANK;
         }
@@ -100,11 +115,17 @@ impl Tile {
         self.remove_flag(tile_flags::BLUE);
     }
 
-    pub fn serialize(&mut self, data: &mut Vec<u8>, _player: &Option<&mut Player>) -> Result<()> {
+    /// `entrance_open` is whether the player it's sent to may walk through friends entrances.
+    pub fn serialize(&mut self, data: &mut Vec<u8>, entrance_open: bool) -> Result<()> {
+        let mut flags = self.flags;
+        if entrance_open && self.get_fore()?.item_type == item_type::FRIENDS_ENTRANCE {
+            flags |= tile_flags::OPEN;
+        }
+
         data.write_u16::<LE>(self.fore)?;
         data.write_u16::<LE>(self.back)?;
         data.write_u16::<LE>(self.parent)?;
-        data.write_u16::<LE>(self.flags)?;
+        data.write_u16::<LE>(flags)?;
 
         if self.has_flag(tile_flags::LOCKED) {
             data.write_u16::<LE>(self.parent)?;
@@ -1,17 +1,143 @@
-use byteorder::WriteBytesExt;
+use byteorder::{WriteBytesExt, LE};
 
 use crate::{
-    consts::tileextra_type,
-    utils::{error::Result, mem},
+    consts::{item_type, lock_flags, tileextra_type},
+    item::iteminfo::ItemInfo,
+    player::player::Player,
+    utils::{self, error::Result, mem},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Letter {
+    pub sender: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum TileExtra {
     None,
-    Door { label: String },
+    Door {
+        label: String,
+        destination: String, // WORLD:DOORID
+        id: String,
+    },
+    Sign {
+        label: String,
+    },
+    Lock {
+        flags: u8,
+        owner_uid: i32,
+        owner_name: String,
+        access: Vec<(i32, String)>, // user ids with their names
+    },
+    Seed {
+        planted_at: u64, // unix time
+        fruit_count: u8,
+    },
+    Mailbox {
+        letters: Vec<Letter>,
+    },
+    Bulletin {
+        letters: Vec<Letter>,
+    },
+    DisplayBlock {
+        item_id: u16,
+    },
+    VendingMachine {
+        item_id: u16,
+        stock: u32,
+        price: i32,
+    },
+    Storage {
+        items: Vec<(u16, u16)>,
+    },
 }
 
 impl TileExtra {
+    /// The extra data a freshly placed `item` starts with, `None` if the item isn't supported yet.
+    pub fn for_item(item: &ItemInfo, player: &Player) -> Option<TileExtra> {
+        let extra = match item.item_type {
+            item_type::DOOR | item_type::PORTAL => TileExtra::Door {
+                label: String::new(),
+                destination: String::new(),
+                id: String::new(),
+            },
+
+            item_type::MAIN_DOOR => TileExtra::main_door(),
+
+            item_type::SIGN => TileExtra::Sign {
+                label: String::new(),
+            },
+
+            item_type::LOCK => TileExtra::Lock {
+                flags: 0,
+                owner_uid: player.user_id,
+                owner_name: player.name.to_owned(),
+                access: Vec::new(),
+            },
+
+            item_type::SEED => TileExtra::Seed {
+                planted_at: utils::unix_time(),
+                fruit_count: utils::random(1..=4),
+            },
+
+            item_type::MAILBOX => TileExtra::Mailbox {
+                letters: Vec::new(),
+            },
+
+            item_type::BULLETIN => TileExtra::Bulletin {
+                letters: Vec::new(),
+            },
+
+            item_type::DISPLAY_BLOCK => TileExtra::DisplayBlock { item_id: 0 },
+
+            item_type::VENDING_MACHINE => TileExtra::VendingMachine {
+                item_id: 0,
+                stock: 0,
+                price: 0,
+            },
+
+            item_type::STORAGE => TileExtra::Storage { items: Vec::new() },
+
+            _ => return None,
+        };
+
+        Some(extra)
+    }
+
+    pub fn main_door() -> TileExtra {
+        TileExtra::Door {
+            label: "EXIT".to_string(),
+            destination: String::new(),
+            id: String::new(),
+        }
+    }
+
+    pub fn is_lock(&self) -> bool {
+        matches!(self, TileExtra::Lock { .. })
+    }
+
+    pub fn is_owner(&self, user_id: i32) -> bool {
+        matches!(self, TileExtra::Lock { owner_uid, .. } if *owner_uid == user_id)
+    }
+
+    /// Whether `user_id` owns the lock or is on its access list, false for anything but locks.
+    pub fn has_access(&self, user_id: i32) -> bool {
+        match self {
+            TileExtra::Lock {
+                flags,
+                owner_uid,
+                access,
+                ..
+            } => {
+                *owner_uid == user_id
+                    || access.iter().any(|(x, _)| *x == user_id)
+                    || flags & lock_flags::PUBLIC != 0
+            }
+            _ => false,
+        }
+    }
+
     pub fn serialize<T>(&self, data: &mut T) -> Result<()>
     where
         T: std::io::Write,
@@ -19,11 +145,75 @@ impl TileExtra {
         match self {
             TileExtra::None => panic!("Tried to serialize None Tileextra!"),
 
-            TileExtra::Door { label } => {
+            TileExtra::Door { label, .. } => {
                 data.write_u8(tileextra_type::DOOR)?;
                 mem::write_string(data, &label)?;
                 data.write_u8(0)?;
             }
+
+            TileExtra::Sign { label } => {
+                data.write_u8(tileextra_type::SIGN)?;
+                mem::write_string(data, label)?;
+                data.write_i32::<LE>(-1)?;
+            }
+
+            TileExtra::Lock {
+                flags,
+                owner_uid,
+                access,
+                ..
+            } => {
+                data.write_u8(tileextra_type::LOCK)?;
+                data.write_u8(*flags)?;
+                data.write_i32::<LE>(*owner_uid)?;
+                data.write_u32::<LE>(access.len() as u32)?;
+
+                for (user_id, _) in access.iter() {
+                    data.write_i32::<LE>(*user_id)?;
+                }
+            }
+
+            TileExtra::Seed {
+                planted_at,
+                fruit_count,
+            } => {
+                data.write_u8(tileextra_type::SEED)?;
+                data.write_u32::<LE>(utils::unix_time().saturating_sub(*planted_at) as u32)?;
+                data.write_u8(*fruit_count)?;
+            }
+
+            // the letters themselves are only shown through dialogs
+            TileExtra::Mailbox { .. } => {
+                data.write_u8(tileextra_type::MAILBOX)?;
+                mem::write_string(data, "")?;
+                mem::write_string(data, "")?;
+                mem::write_string(data, "")?;
+                data.write_u8(0)?;
+            }
+
+            TileExtra::Bulletin { .. } => {
+                data.write_u8(tileextra_type::BULLETIN)?;
+                mem::write_string(data, "")?;
+                mem::write_string(data, "")?;
+                mem::write_string(data, "")?;
+                data.write_u8(0)?;
+            }
+
+            TileExtra::DisplayBlock { item_id } => {
+                data.write_u8(tileextra_type::DISPLAY_BLOCK)?;
+                data.write_u32::<LE>(*item_id as u32)?;
+            }
+
+            TileExtra::VendingMachine { item_id, price, .. } => {
+                data.write_u8(tileextra_type::VENDING_MACHINE)?;
+                data.write_u32::<LE>(*item_id as u32)?;
+                data.write_i32::<LE>(*price)?;
+            }
+
+            TileExtra::Storage { .. } => {
+                data.write_u8(tileextra_type::STORAGE)?;
+                data.write_u8(0)?;
+            }
         }
 
         Ok(())
@@ -1,4 +1,9 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Instant};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    rc::Rc,
+    time::Instant,
+};
 
 use byteorder::{WriteBytesExt, LE};
 use enet_sys::{
@@ -6,9 +11,14 @@ use enet_sys::{
 };
 
 use crate::{
-    consts::{self, items, message_type, packet::TankUpdatePacket, packet_flags, packet_type},
+    config::config::CONFIG,
+    consts::{
+        self, items, message_type, packet::TankUpdatePacket, packet_flags, packet_type,
+        permissions, tile_flags,
+    },
     enet_wrapper::peer::ENetPeer,
-    player::player::Player,
+    item::iteminfo_manager::ITEM_MANAGER,
+    player::{inventoryitem, player::Player},
     utils::{
         self,
         error::{Error, Result},
@@ -22,6 +32,10 @@ use crate::{
 
 use super::{tile::Tile, worldobject::WorldObject};
 
+// net ids used by ITEM_CHANGE_OBJECT, anything else means the object got collected by that player
+const OBJECT_CREATE: i32 = -1;
+const OBJECT_MODIFY: i32 = -3;
+
 pub struct World {
     pub secret1: u32,
     pub name: String,
@@ -37,6 +51,12 @@ pub struct World {
     pub last_net_id: i32,
     pub last_object_id: i32,
     pub weather_base_id: u32,
+
+    // tile index of the world lock, not saved since `find_world_lock` gets it back from the tiles
+    pub world_lock: Option<u32>,
+
+    // set whenever something that gets saved changes, cleared by `worlddb::save_world`
+    pub dirty: bool,
 }
 
 impl World {
@@ -56,6 +76,8 @@ impl World {
             last_net_id: 0,
             last_object_id: 0,
             weather_base_id: 0,
+            world_lock: None,
+            dirty: false,
         }
     }
 
@@ -75,38 +97,40 @@ impl World {
             last_net_id: 0,
             last_object_id: 0,
             weather_base_id: 0,
+            world_lock: None,
+            dirty: false,
         }
     }
 
     pub fn generate_normal(name: String) -> Rc<RefCell<Self>> {
-        const WIDTH: u32 = 100;
-        const HEIGHT: u32 = 60;
+        let width = CONFIG.world_width;
+        let height = CONFIG.world_height;
 
-        const LAVA_LAYER: u32 = HEIGHT - 10;
-        const ROCK_LAYER: u32 = (HEIGHT / 2) + 2;
-        const START_LAYER: u32 = HEIGHT / 2;
-        const BEDROCK_LAYER: u32 = HEIGHT - 5;
+        let lava_layer = height - 10;
+        let rock_layer = (height / 2) + 2;
+        let start_layer = height / 2;
+        let bedrock_layer = height - 5;
 
-        let world = Rc::new(RefCell::new(World::new(name, WIDTH, HEIGHT)));
+        let world = Rc::new(RefCell::new(World::new(name, width, height)));
 
         let main_door_pos = Vec2u {
-            x: utils::random(0..WIDTH),
-            y: START_LAYER - 1,
+            x: utils::random(0..width),
+            y: start_layer - 1,
         };
 
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
+        for y in 0..height {
+            for x in 0..width {
                 let mut tile = Tile::new(Rc::downgrade(&world), x, y);
                 let mut reference = world.borrow_mut();
 
-                if y >= START_LAYER {
+                if y >= start_layer {
                     tile.set_back(items::CAVE_BACKGROUND);
 
-                    if y >= BEDROCK_LAYER {
+                    if y >= bedrock_layer {
                         tile.set_fore(items::BEDROCK);
                     } else if main_door_pos.x == x && main_door_pos.y + 1 == y {
                         tile.set_fore(items::BEDROCK);
-                    } else if y <= BEDROCK_LAYER && y >= LAVA_LAYER {
+                    } else if y <= bedrock_layer && y >= lava_layer {
                         let chance: i32 = utils::random(0..75);
 
                         if chance > 20 {
@@ -116,7 +140,7 @@ impl World {
                         } else {
                             tile.set_fore(items::ROCK);
                         }
-                    } else if y >= ROCK_LAYER {
+                    } else if y >= rock_layer {
                         let chance: i32 = utils::random(0..55);
 
                         if chance <= 1 {
@@ -129,10 +153,8 @@ impl World {
                     }
                 } else if main_door_pos.x == x && main_door_pos.y == y {
                     tile.set_fore(items::MAIN_DOOR);
-                    tile.extra = TileExtra::Door {
-                        label: "EXIT".to_string(),
-                    };
-                    
+                    tile.extra = TileExtra::main_door();
+
                     reference.door_pos = Vec2f {
                         x: (x * 32) as f32,
                         y: (y * 32) as f32,
@@ -240,6 +262,248 @@ impl World {
         self.send_all();
     }
 
+    /// Sends the tile again including its extra data, for when it changed without being rebuilt.
+    pub fn push_tile_update(&mut self, x: u32, y: u32) -> Result<()> {
+        let mut data = Vec::<u8>::with_capacity(64);
+        self.get_tile_safe(x, y)?.serialize(&mut data, false)?;
+
+        let mut tankpacket = TankUpdatePacket::with_extra_data(data);
+        tankpacket.packet_type = packet_type::SEND_TILE_UPDATE_DATA;
+        tankpacket.tile_x = x as i32;
+        tankpacket.tile_y = y as i32;
+        self.push_tankpacket(tankpacket);
+
+        Ok(())
+    }
+
+    /// Drops `count` of `item_id` at `pos`, merging it into an object of the same item on that tile if it fits.
+    pub fn drop_object(&mut self, item_id: u16, count: u16, pos: Vec2f) {
+        let tile_of = |pos: Vec2f| ((pos.x / 32.0) as i32, (pos.y / 32.0) as i32);
+        let max_count = ITEM_MANAGER
+            .get_item_safe(item_id as u32)
+            .map_or(CONFIG.max_items_count, |item| item.max_count());
+
+        let merge = self.objects.iter_mut().find(|(_, object)| {
+            object.item_id == item_id
+                && tile_of(object.pos) == tile_of(pos)
+                && object.count as u32 + count as u32 <= max_count as u32
+        });
+
+        let mut tankpacket = TankUpdatePacket::default();
+        tankpacket.packet_type = packet_type::ITEM_CHANGE_OBJECT;
+        tankpacket.int_val = item_id as i32;
+
+        match merge {
+            Some((&id, object)) => {
+                object.count += count;
+
+                tankpacket.net_id = OBJECT_MODIFY;
+                tankpacket.target_id = id as i32;
+                tankpacket.pos_x = object.pos.x;
+                tankpacket.pos_y = object.pos.y;
+                tankpacket.float_val = object.count as f32;
+            }
+
+            None => {
+                self.last_object_id += 1;
+                self.objects.insert(
+                    self.last_object_id as u32,
+                    WorldObject {
+                        pos,
+                        count,
+                        flags: 0,
+                        item_id,
+                    },
+                );
+
+                tankpacket.net_id = OBJECT_CREATE;
+                tankpacket.target_id = -1;
+                tankpacket.pos_x = pos.x;
+                tankpacket.pos_y = pos.y;
+                tankpacket.float_val = count as f32;
+            }
+        }
+
+        self.dirty = true;
+        self.push_tankpacket(tankpacket);
+    }
+
+    /// Takes `count` out of the object, removing it once nothing is left. `net_id` is whoever collected it.
+    pub fn take_object(&mut self, id: u32, count: u16, net_id: i32) -> Result<()> {
+        let object = self.objects.get_mut(&id).ok_or(Error::NotFound)?;
+        if count > object.count {
+            return Err(Error::ItemCountNegative);
+        }
+
+        object.count -= count;
+
+        let mut tankpacket = TankUpdatePacket::default();
+        tankpacket.packet_type = packet_type::ITEM_CHANGE_OBJECT;
+
+        if object.count == 0 {
+            tankpacket.net_id = net_id;
+            tankpacket.target_id = -1;
+            tankpacket.int_val = id as i32;
+            self.objects.remove(&id);
+        } else {
+            tankpacket.net_id = OBJECT_MODIFY;
+            tankpacket.target_id = id as i32;
+            tankpacket.int_val = object.item_id as i32;
+            tankpacket.pos_x = object.pos.x;
+            tankpacket.pos_y = object.pos.y;
+            tankpacket.float_val = object.count as f32;
+        }
+
+        self.dirty = true;
+        self.push_tankpacket(tankpacket);
+
+        Ok(())
+    }
+
+    /// Where to spawn for the door with the given ID, if there is one.
+    pub fn find_door(&self, door_id: &str) -> Option<Vec2f> {
+        self.tiles
+            .iter()
+            .find(|tile| matches!(&tile.extra, TileExtra::Door { id, .. } if id.eq_ignore_ascii_case(door_id)))
+            .map(|tile| Vec2f::new((tile.pos.x * 32) as f32, (tile.pos.y * 32) as f32))
+    }
+
+    pub fn find_world_lock(&self) -> Option<u32> {
+        self.tiles
+            .iter()
+            .position(|tile| {
+                tile.extra.is_lock() && tile.get_fore().map_or(false, |x| x.is_world_lock())
+            })
+            .map(|index| index as u32)
+    }
+
+    /// The lock protecting the tile, its area lock if it has one, otherwise the world lock.
+    pub fn get_lock(&self, x: u32, y: u32) -> Option<&TileExtra> {
+        let index = (x + y * self.width) as usize;
+        let tile = self.tiles.get(index)?;
+
+        let lock_index = if tile.extra.is_lock() {
+            index
+        } else if tile.has_flag(tile_flags::LOCKED) {
+            tile.parent as usize
+        } else {
+            self.world_lock? as usize
+        };
+
+        Some(&self.tiles.get(lock_index)?.extra).filter(|extra| extra.is_lock())
+    }
+
+    /// Owner of the world lock, if there is one.
+    pub fn owner(&self) -> Option<i32> {
+        match &self.tiles.get(self.world_lock? as usize)?.extra {
+            TileExtra::Lock { owner_uid, .. } => Some(*owner_uid),
+            _ => None,
+        }
+    }
+
+    /// Friends entrances only let the owner and their friends through, or everyone if nobody owns the world.
+    pub fn can_pass_entrance(&self, player: &Player) -> bool {
+        match self.owner() {
+            Some(owner) => {
+                owner == player.user_id
+                    || player.is_friend(owner)
+                    || player.has_permission(permissions::BYPASS_LOCKS)
+            }
+            None => true,
+        }
+    }
+
+    pub fn can_edit(&self, player: &Player, x: u32, y: u32) -> bool {
+        if player.has_permission(permissions::BYPASS_LOCKS) {
+            return true;
+        }
+
+        match self.get_lock(x, y) {
+            Some(lock) => lock.has_access(player.user_id),
+            None => true,
+        }
+    }
+
+    /// Claims up to `size` free tiles around the area lock at `index` by flood-filling from it.
+    pub fn claim_area(&mut self, index: u32, size: usize) -> Vec<u16> {
+        let mut claimed = Vec::with_capacity(size);
+        let mut visited = HashSet::from([index]);
+        let mut queue = VecDeque::from([index]);
+
+        while let Some(current) = queue.pop_front() {
+            let (x, y) = (current % self.width, current / self.width);
+
+            let mut neighbours = Vec::with_capacity(4);
+            if x > 0 {
+                neighbours.push(current - 1);
+            }
+            if x + 1 < self.width {
+                neighbours.push(current + 1);
+            }
+            if y > 0 {
+                neighbours.push(current - self.width);
+            }
+            if y + 1 < self.height {
+                neighbours.push(current + self.width);
+            }
+
+            for next in neighbours {
+                if claimed.len() >= size {
+                    break;
+                }
+
+                if !visited.insert(next) {
+                    continue;
+                }
+
+                // already claimed by another lock
+                let tile = &mut self.tiles[next as usize];
+                if tile.has_flag(tile_flags::LOCKED) || tile.extra.is_lock() {
+                    continue;
+                }
+
+                tile.parent = index as u16;
+                tile.add_flag(tile_flags::LOCKED);
+                claimed.push(next as u16);
+                queue.push_back(next);
+            }
+        }
+
+        self.dirty = true;
+        claimed
+    }
+
+    /// Frees every tile claimed by the area lock at `index`.
+    pub fn release_area(&mut self, index: u32) -> Vec<u16> {
+        let mut released = Vec::new();
+
+        for (i, tile) in self.tiles.iter_mut().enumerate() {
+            if tile.has_flag(tile_flags::LOCKED) && tile.parent as u32 == index {
+                tile.parent = 0;
+                tile.remove_flag(tile_flags::LOCKED);
+                released.push(i as u16);
+            }
+        }
+
+        self.dirty = true;
+        released
+    }
+
+    pub fn push_lock_update(&mut self, x: u32, y: u32, lock_id: u16, net_id: i32, tiles: &[u16]) {
+        let mut data = Vec::<u8>::with_capacity(tiles.len() * 2);
+        for index in tiles {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+
+        let mut tankpacket = TankUpdatePacket::with_extra_data(data);
+        tankpacket.packet_type = packet_type::SEND_LOCK;
+        tankpacket.net_id = net_id;
+        tankpacket.int_val = lock_id as i32;
+        tankpacket.tile_x = x as i32;
+        tankpacket.tile_y = y as i32;
+        self.push_tankpacket(tankpacket);
+    }
+
     pub fn get_tile_safe<'a>(&'a mut self, x: u32, y: u32) -> Result<&'a mut Tile> {
         let index = x + y * self.width;
         if index >= self.tiles.len() as u32 {
@@ -280,8 +544,9 @@ impl World {
         data.write_u32::<LE>(self.height)?;
         data.write_u32::<LE>(self.width * self.height)?;
 
+        let entrance_open = player.map_or(false, |player| self.can_pass_entrance(player));
         for tile in self.tiles.iter_mut() {
-            tile.serialize(data, &player)?;
+            tile.serialize(data, entrance_open)?;
         }
 
         let objects_count = self.objects.len() as u32;
@@ -293,7 +558,7 @@ impl World {
             data.write_u16::<LE>(object.item_id)?;
             data.write_f32::<LE>(object.pos.x)?;
             data.write_f32::<LE>(object.pos.y)?;
-            data.write_u8(object.count)?;
+            data.write_u8(inventoryitem::client_count(object.count))?;
             data.write_u8(object.flags)?;
         }
 
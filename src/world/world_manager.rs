@@ -1,7 +1,6 @@
 use std::{
     cell::{RefCell, RefMut},
     collections::HashMap,
-    path::Path,
     rc::Rc,
 };
 
@@ -46,8 +45,11 @@ impl WorldManager {
         if !self.worlds.contains_key(&name) {
             let world: Rc<RefCell<World>>;
 
-            if Path::new(&format!("data/worlds/{}.bin", name)).exists() {
+            if worlddb::world_exists(&name).unwrap_or(false) {
                 world = worlddb::load_world(&name).unwrap(); // unwrap incase i fucked something
+
+                let world_lock = world.borrow().find_world_lock();
+                world.borrow_mut().world_lock = world_lock;
             } else {
                 match world_type {
                     WORLD_TYPE_NORMAL => {
@@ -66,6 +68,26 @@ impl WorldManager {
         self.worlds.get(&name).unwrap().clone()
     }
 
+    /// Saves every loaded world, or only the ones that changed since they were last saved.
+    pub fn save_worlds(&self, force: bool) -> usize {
+        let mut count = 0;
+
+        for world in self.worlds.values() {
+            let world = world.borrow_mut();
+            if !force && !world.dirty {
+                continue;
+            }
+
+            let name = world.name.to_owned();
+            match worlddb::save_world(world) {
+                Ok(_) => count += 1,
+                Err(e) => println!("Failed to save world {}! Error: {:?}", name, e),
+            }
+        }
+
+        count
+    }
+
     pub fn is_valid(&self, player: &mut Player, name: &String) -> bool {
         let err: &str;
         if name.is_empty() {
@@ -1,8 +1,9 @@
 use crate::utils::math::Vec2f;
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct WorldObject {
     pub pos: Vec2f,
-    pub count: u8,
+    pub count: u16,
     pub flags: u8,
     pub item_id: u16,
 }